@@ -0,0 +1,142 @@
+//! Expected answers of each day, read from a file like `answers.toml`:
+//!
+//! ```toml
+//! [day1]
+//! part1 = 24000
+//! part2 = 45000
+//!
+//! [day5]
+//! part1 = "CMZ"
+//!
+//! [day10]
+//! part2 = [
+//!     "##..##..",
+//!     "###...##",
+//! ]
+//! ```
+//!
+//! Only this subset of TOML is supported: `[dayN]` tables with `part1`/`part2` keys whose values are
+//! integers, strings without escapes or arrays of strings (for [`Answer::Screen`]), and whole-line comments.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::parser::{self, ParseError};
+use crate::Answer;
+
+/// File name of the answers looked up in the data directory.
+pub const FILE_NAME: &str = "answers.toml";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers(pub BTreeMap<(u8, u8), Answer>);
+
+impl Answers {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut answers = Self::default();
+        let mut day = None;
+        let mut lines = input.lines();
+        while let Some(line) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[') {
+                let table = parser::strip_prefix(input, table, "day")?;
+                let (n, _) = parser::split_once(input, table, "]")?;
+                day = Some(parser::number::<u8>(input, n)?);
+                continue;
+            }
+
+            let (key, value) = parser::split_once(input, line, "=")?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                _ => return Err(ParseError::at(input, key, "'part1' or 'part2'")),
+            };
+            let Some(day) = day else {
+                return Err(ParseError::at(input, line, "a '[dayN]' table before the answers"));
+            };
+
+            let value = value.trim();
+            let answer = if let Some(first) = value.strip_prefix('[') {
+                // arrays can span several lines, one string per line
+                let mut rows = Vec::new();
+                let mut rest = first;
+                loop {
+                    for item in rest.split(',').map(str::trim).filter(|s| !s.is_empty() && *s != "]") {
+                        rows.push(parse_string(input, item.trim_end_matches(']').trim_end())?.to_string());
+                    }
+                    if rest.trim_end().ends_with(']') {
+                        break;
+                    }
+                    rest = lines.next().ok_or_else(|| ParseError::after(input, input, "']'"))?;
+                }
+                Answer::Screen(rows)
+            } else if value.starts_with('"') {
+                Answer::Str(parse_string(input, value)?.to_string())
+            } else {
+                Answer::Int(value.replace('_', "").parse().map_err(|_| {
+                    ParseError::at(input, value, "an integer, a string or an array of strings")
+                })?)
+            };
+            answers.0.insert((day, part), answer);
+        }
+        Ok(answers)
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.0.get(&(day, part))
+    }
+}
+
+fn parse_string<'a>(input: &str, s: &'a str) -> Result<&'a str, ParseError> {
+    let inner = parser::strip_prefix(input, s, "\"")?;
+    match inner.strip_suffix('"') {
+        Some(inner) if !inner.contains(['"', '\\']) => Ok(inner),
+        _ => Err(ParseError::at(input, s, "a string without escapes")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(concat!(
+            "# example answers\n",
+            "[day1]\n",
+            "part1 = 24_000\n",
+            "part2 = -45000\n",
+            "\n",
+            "[day5]\n",
+            "part1 = \"CMZ\"\n",
+            "[day10]\n",
+            "part2 = [\n",
+            "    \"##..#\",\n",
+            "    \"#.#..\",\n",
+            "]\n",
+            "[day11]\n",
+            "part1 = [\"#\", \".\"]\n",
+        )).unwrap();
+
+        assert_eq!(answers.get(1, 1), Some(&Answer::Int(24000)));
+        assert_eq!(answers.get(1, 2), Some(&Answer::Int(-45000)));
+        assert_eq!(answers.get(5, 1), Some(&Answer::Str("CMZ".into())));
+        assert_eq!(answers.get(5, 2), None);
+        assert_eq!(answers.get(10, 2), Some(&Answer::Screen(vec!["##..#".into(), "#.#..".into()])));
+        assert_eq!(answers.get(11, 1), Some(&Answer::Screen(vec!["#".into(), ".".into()])));
+
+        let err = Answers::parse("[day1]\npart1 = 12\npart3 = 4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        let err = Answers::parse("part1 = 12\n").unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+}
@@ -0,0 +1,197 @@
+//! Repeated timing of the parse and solve steps of a day, compared against a stored baseline.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::input::InputSource;
+use crate::parser::{self, ParseError};
+use crate::Day;
+
+/// Baseline file used when `--baseline` isn't given.
+pub const DEFAULT_BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/bench-baseline.txt");
+
+/// Step of a day that is timed on its own.
+pub const STEPS: [&str; 3] = ["parse", "part1", "part2"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        assert!(!samples.is_empty(), "no samples");
+        samples.sort_unstable();
+
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) { (samples[n / 2 - 1] + samples[n / 2]) / 2 } else { samples[n / 2] };
+        let mean = samples.iter().map(Duration::as_secs_f64).sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|s| (s.as_secs_f64() - mean).powi(2)).sum::<f64>() / n as f64;
+        Self {
+            min: samples[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// Stats of each of the [`STEPS`] of a day.
+pub type DayStats = [Stats; 3];
+
+/// Runs `warmup` untimed iterations of the day and then times `iterations` runs of each step.
+pub fn run(day: &Day, input: &str, source: &InputSource, warmup: usize, iterations: usize) -> Result<DayStats, ParseError> {
+    let solution = day.solution;
+    let parsed = solution.parse_from(input, source)?;
+    for _ in 0..warmup {
+        black_box(solution.parse_from(input, source)?);
+        black_box(solution.part1(&*parsed));
+        black_box(solution.part2(&*parsed));
+    }
+
+    let mut samples = [(); 3].map(|_| Vec::with_capacity(iterations));
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        black_box(solution.parse_from(input, source)?);
+        samples[0].push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(solution.part1(&*parsed));
+        samples[1].push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(solution.part2(&*parsed));
+        samples[2].push(start.elapsed());
+    }
+    Ok(samples.map(|mut s| Stats::from_samples(&mut s)))
+}
+
+/// Name under which the runs on the inputs of `source` are stored in a [`Baseline`], so that the
+/// examples aren't compared against the puzzle inputs.
+pub fn input_kind(source: &InputSource) -> &'static str {
+    match source {
+        InputSource::Example => "example",
+        _ => "input",
+    }
+}
+
+/// Stats of previous runs, keyed by day, [`input_kind`] and step.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Baseline(pub BTreeMap<(u8, String, String), Stats>);
+
+impl Baseline {
+    /// Loads the baseline at `path`, or an empty one if the file doesn't exist yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parses lines of `<day> <input> <step> <min> <median> <mean> <std dev>`, with the times in
+    /// nanoseconds.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut baseline = Self::default();
+        for line in input.lines().filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let fields = line.split(' ').collect::<Vec<_>>();
+            let [day, kind, step, times @ ..] = fields.as_slice() else {
+                return Err(ParseError::at(input, line, "'<day> <input> <step> <min> <median> <mean> <std dev>'"));
+            };
+            let [min, median, mean, std_dev] = times else {
+                return Err(ParseError::after(input, line, "4 times in nanoseconds"));
+            };
+            let nanos = |s: &str| parser::number(input, s).map(Duration::from_nanos);
+            baseline.0.insert((parser::number(input, day)?, kind.to_string(), step.to_string()), Stats {
+                min: nanos(min)?,
+                median: nanos(median)?,
+                mean: nanos(mean)?,
+                std_dev: nanos(std_dev)?,
+            });
+        }
+        Ok(baseline)
+    }
+
+    pub fn get(&self, day: u8, kind: &str, step: &str) -> Option<&Stats> {
+        self.0.get(&(day, kind.to_string(), step.to_string()))
+    }
+
+    pub fn insert(&mut self, day: u8, kind: &str, stats: &DayStats) {
+        for (step, stats) in STEPS.iter().zip(stats) {
+            self.0.insert((day, kind.to_string(), step.to_string()), *stats);
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(w, "# day input step min median mean std_dev (ns)")?;
+        for ((day, kind, step), s) in &self.0 {
+            writeln!(w, "{day} {kind} {step} {} {} {} {}", s.min.as_nanos(), s.median.as_nanos(), s.mean.as_nanos(), s.std_dev.as_nanos())?;
+        }
+        w.flush()
+    }
+}
+
+/// Relative change of the median against a baseline, in percent.
+pub fn change(stats: &Stats, baseline: &Stats) -> f64 {
+    (stats.median.as_secs_f64() / baseline.median.as_secs_f64() - 1.0) * 100.0
+}
+
+/// Displays a duration with a unit that keeps it readable, e.g. `812 ns`, `12.4 μs` or `3.20 s`.
+pub struct HumanDuration(pub Duration);
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos() as f64;
+        let s = match nanos {
+            n if n < 1e3 => format!("{n} ns"),
+            n if n < 1e6 => format!("{:.1} μs", n / 1e3),
+            n if n < 1e9 => format!("{:.1} ms", n / 1e6),
+            n => format!("{:.2} s", n / 1e9),
+        };
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut samples = [4, 1, 3, 2].map(Duration::from_micros);
+        let stats = Stats::from_samples(&mut samples);
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.median, Duration::from_nanos(2500));
+        assert_eq!(stats.mean, Duration::from_nanos(2500));
+        assert_eq!(stats.std_dev.as_nanos(), 1118);
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let mut baseline = Baseline::default();
+        let stats = Stats::from_samples(&mut [Duration::from_millis(3), Duration::from_millis(5)]);
+        baseline.insert(16, "input", &[stats; 3]);
+
+        let path = std::env::temp_dir().join(format!("aoc-bench-test-{}.txt", std::process::id()));
+        baseline.save(&path).unwrap();
+        let loaded = Baseline::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, baseline);
+        assert_eq!(change(&stats, loaded.get(16, "input", "part2").unwrap()), 0.0);
+        assert_eq!(loaded.get(16, "example", "part2"), None);
+
+        let err = Baseline::parse("1 input parse 1 2 3 4\n2 input part1 1 x 3 4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 17));
+    }
+}
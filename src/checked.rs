@@ -0,0 +1,113 @@
+//! Arithmetic of the days whose values can grow past their integer types, such as the monkey values
+//! of day 21 or the resource counts of day 19.
+//!
+//! By default these are the plain operators, wrapping silently in release builds. With the
+//! `checked-arith` feature every operation is checked instead, and an overflow panics with the day,
+//! the operation and its operands, e.g. `day 21: overflow in 3037000500 * 3037000500 (i64)`.
+
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Whether the operations are checked, i.e. the `checked-arith` feature is enabled.
+pub const ENABLED: bool = cfg!(feature = "checked-arith");
+
+pub trait Int: Copy + Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> {
+    const NAME: &'static str;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn to_i128(self) -> i128;
+    /// `n as Self`.
+    fn from_i128_wrapping(n: i128) -> Self;
+    fn try_from_i128(n: i128) -> Option<Self>;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),+) => {$(
+        impl Int for $t {
+            const NAME: &'static str = stringify!($t);
+
+            fn checked_add(self, rhs: Self) -> Option<Self> { <$t>::checked_add(self, rhs) }
+            fn checked_sub(self, rhs: Self) -> Option<Self> { <$t>::checked_sub(self, rhs) }
+            fn checked_mul(self, rhs: Self) -> Option<Self> { <$t>::checked_mul(self, rhs) }
+            fn checked_div(self, rhs: Self) -> Option<Self> { <$t>::checked_div(self, rhs) }
+            fn to_i128(self) -> i128 { self as i128 }
+            fn from_i128_wrapping(n: i128) -> Self { n as $t }
+            fn try_from_i128(n: i128) -> Option<Self> { <$t>::try_from(n).ok() }
+        }
+    )+};
+}
+
+impl_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[cold]
+#[inline(never)]
+fn overflow<T: Int>(day: u8, lhs: T, op: &str, rhs: T) -> ! {
+    panic!("day {day}: overflow in {lhs} {op} {rhs} ({})", T::NAME)
+}
+
+#[inline]
+pub fn add<T: Int>(day: u8, lhs: T, rhs: T) -> T {
+    if ENABLED { lhs.checked_add(rhs).unwrap_or_else(|| overflow(day, lhs, "+", rhs)) } else { lhs + rhs }
+}
+
+#[inline]
+pub fn sub<T: Int>(day: u8, lhs: T, rhs: T) -> T {
+    if ENABLED { lhs.checked_sub(rhs).unwrap_or_else(|| overflow(day, lhs, "-", rhs)) } else { lhs - rhs }
+}
+
+#[inline]
+pub fn mul<T: Int>(day: u8, lhs: T, rhs: T) -> T {
+    if ENABLED { lhs.checked_mul(rhs).unwrap_or_else(|| overflow(day, lhs, "*", rhs)) } else { lhs * rhs }
+}
+
+/// `lhs / rhs`, which also overflows when dividing by zero or `MIN` by -1.
+#[inline]
+pub fn div<T: Int>(day: u8, lhs: T, rhs: T) -> T {
+    if ENABLED { lhs.checked_div(rhs).unwrap_or_else(|| overflow(day, lhs, "/", rhs)) } else { lhs / rhs }
+}
+
+/// `n as U`, failing if `n` doesn't fit in a `U`.
+#[inline]
+pub fn cast<T: Int, U: Int>(day: u8, n: T) -> U {
+    if ENABLED {
+        U::try_from_i128(n.to_i128())
+            .unwrap_or_else(|| panic!("day {day}: overflow in {n} as {} ({})", U::NAME, T::NAME))
+    } else {
+        U::from_i128_wrapping(n.to_i128())
+    }
+}
+
+/// Sum of `items`, each addition being checked like [`add`].
+pub fn sum<T: Int>(day: u8, items: impl IntoIterator<Item = T>) -> T {
+    items.into_iter().reduce(|acc, n| add(day, acc, n)).unwrap_or_else(|| T::from_i128_wrapping(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_range() {
+        assert_eq!(mul(1, 6i64, -7), -42);
+        assert_eq!(sub(1, 7u16, 7), 0);
+        assert_eq!(cast::<i64, isize>(1, -5), -5);
+        assert_eq!(sum(1, [1u8, 2, 3]), 6);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-arith")]
+    #[should_panic(expected = "day 21: overflow in 3037000500 * 3037000500 (i64)")]
+    fn test_overflow() {
+        mul(21, 3037000500i64, 3037000500);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-arith")]
+    #[should_panic(expected = "day 20: overflow in 300 as u8 (i64)")]
+    fn test_cast_overflow() {
+        cast::<i64, u8>(20, 300);
+    }
+}
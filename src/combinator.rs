@@ -0,0 +1,212 @@
+//! Parser combinators for the wordier puzzle formats, which can then be written as a grammar such as
+//! `(preceded(tag("x="), number()), preceded(tag(", y="), number()))` instead of a chain of
+//! `split_once`.
+//!
+//! A [`Parser`] is any function from the rest of the line to a value and what follows it, and tuples
+//! of parsers parse each of their elements in order. Failures keep where they happened and are turned
+//! into [`ParseError`]s by [`parse_line`], like the ones of the [`parser`](crate::parser) helpers.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use crate::parser::ParseError;
+
+/// What a parser expected and where it didn't find it, `at` being the rest of the input at that point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure<'a> {
+    pub at: &'a str,
+    pub expected: Cow<'static, str>,
+}
+
+impl<'a> Failure<'a> {
+    pub fn new(at: &'a str, expected: impl Into<Cow<'static, str>>) -> Self {
+        Self { at, expected: expected.into() }
+    }
+}
+
+/// The parsed value and the rest of the input, or where parsing failed.
+pub type PResult<'a, T> = Result<(T, &'a str), Failure<'a>>;
+
+pub trait Parser<'a, T> {
+    fn parse(&self, s: &'a str) -> PResult<'a, T>;
+}
+
+impl<'a, T, F: Fn(&'a str) -> PResult<'a, T>> Parser<'a, T> for F {
+    fn parse(&self, s: &'a str) -> PResult<'a, T> {
+        self(s)
+    }
+}
+
+macro_rules! impl_parser_for_tuple {
+    ($($p:ident: $P:ident => $T:ident),+) => {
+        impl<'a, $($T, $P: Parser<'a, $T>),+> Parser<'a, ($($T,)+)> for ($($P,)+) {
+            fn parse(&self, s: &'a str) -> PResult<'a, ($($T,)+)> {
+                let ($($p,)+) = self;
+                $(let ($p, s) = $p.parse(s)?;)+
+                Ok((($($p,)+), s))
+            }
+        }
+    };
+}
+
+impl_parser_for_tuple!(p1: P1 => T1, p2: P2 => T2);
+impl_parser_for_tuple!(p1: P1 => T1, p2: P2 => T2, p3: P3 => T3);
+impl_parser_for_tuple!(p1: P1 => T1, p2: P2 => T2, p3: P3 => T3, p4: P4 => T4);
+impl_parser_for_tuple!(p1: P1 => T1, p2: P2 => T2, p3: P3 => T3, p4: P4 => T4, p5: P5 => T5);
+impl_parser_for_tuple!(p1: P1 => T1, p2: P2 => T2, p3: P3 => T3, p4: P4 => T4, p5: P5 => T5, p6: P6 => T6);
+impl_parser_for_tuple!(p1: P1 => T1, p2: P2 => T2, p3: P3 => T3, p4: P4 => T4, p5: P5 => T5, p6: P6 => T6, p7: P7 => T7);
+
+/// Parses all of `line`, a sub-slice of `input`, with `parser`, failing if anything is left after it.
+pub fn parse_line<'a, T>(input: &str, line: &'a str, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+    let result = parser.parse(line).and_then(|(value, rest)| match rest {
+        "" => Ok(value),
+        rest => Err(Failure::new(rest, "end of line")),
+    });
+    result.map_err(|f| ParseError::at(input, f.at, f.expected))
+}
+
+/// The literal `lit`.
+pub fn tag<'a>(lit: &'static str) -> impl Fn(&'a str) -> PResult<'a, &'a str> {
+    move |s: &'a str| match s.strip_prefix(lit) {
+        Some(rest) => Ok((&s[..lit.len()], rest)),
+        None => Err(Failure::new(s, format!("'{lit}'"))),
+    }
+}
+
+/// One or more characters matching `pred`.
+pub fn take_while1<'a>(pred: impl Fn(char) -> bool, expected: &'static str) -> impl Fn(&'a str) -> PResult<'a, &'a str> {
+    move |s: &'a str| match s.find(|c| !pred(c)).unwrap_or(s.len()) {
+        0 => Err(Failure::new(s, expected)),
+        end => Ok(s.split_at(end)),
+    }
+}
+
+/// A word made of ASCII letters, digits and underscores.
+pub fn ident<'a>() -> impl Fn(&'a str) -> PResult<'a, &'a str> {
+    take_while1(|c| c.is_ascii_alphanumeric() || c == '_', "an identifier")
+}
+
+/// Any amount of whitespace, including none.
+pub fn ws<'a>() -> impl Fn(&'a str) -> PResult<'a, ()> {
+    |s: &'a str| Ok(((), s.trim_start()))
+}
+
+/// A decimal number, with a leading `-` if negative, that fits in a `T`.
+pub fn number<'a, T: FromStr>() -> impl Fn(&'a str) -> PResult<'a, T> {
+    |s: &'a str| {
+        let digits_start = if s.starts_with('-') { 1 } else { 0 };
+        let end = s[digits_start..].find(|c: char| !c.is_ascii_digit()).map_or(s.len(), |i| digits_start + i);
+        match s[..end].parse() {
+            Ok(n) => Ok((n, &s[end..])),
+            Err(_) => Err(Failure::new(s, "a number")),
+        }
+    }
+}
+
+/// The value of `parser` converted with `f`.
+pub fn map<'a, T, U>(parser: impl Parser<'a, T>, f: impl Fn(T) -> U) -> impl Fn(&'a str) -> PResult<'a, U> {
+    move |s: &'a str| parser.parse(s).map(|(value, rest)| (f(value), rest))
+}
+
+/// The value of `parser`, failing with `expected` if it doesn't satisfy `pred`.
+pub fn verify<'a, T>(parser: impl Parser<'a, T>, pred: impl Fn(&T) -> bool, expected: &'static str) -> impl Fn(&'a str) -> PResult<'a, T> {
+    move |s: &'a str| match parser.parse(s)? {
+        (value, rest) if pred(&value) => Ok((value, rest)),
+        _ => Err(Failure::new(s, expected)),
+    }
+}
+
+/// `parser` after `prefix`, whose value is dropped.
+pub fn preceded<'a, P, T>(prefix: impl Parser<'a, P>, parser: impl Parser<'a, T>) -> impl Fn(&'a str) -> PResult<'a, T> {
+    move |s: &'a str| {
+        let (_, s) = prefix.parse(s)?;
+        parser.parse(s)
+    }
+}
+
+/// `parser` followed by `suffix`, whose value is dropped.
+pub fn terminated<'a, T, S>(parser: impl Parser<'a, T>, suffix: impl Parser<'a, S>) -> impl Fn(&'a str) -> PResult<'a, T> {
+    move |s: &'a str| {
+        let (value, s) = parser.parse(s)?;
+        let (_, s) = suffix.parse(s)?;
+        Ok((value, s))
+    }
+}
+
+/// `parser` between `prefix` and `suffix`.
+pub fn delimited<'a, P, T, S>(prefix: impl Parser<'a, P>, parser: impl Parser<'a, T>, suffix: impl Parser<'a, S>) -> impl Fn(&'a str) -> PResult<'a, T> {
+    terminated(preceded(prefix, parser), suffix)
+}
+
+/// `first`, or `second` if it fails. When both fail the error is the one that got the furthest, or
+/// expects either of them if they failed at the same place.
+pub fn alt<'a, T>(first: impl Parser<'a, T>, second: impl Parser<'a, T>) -> impl Fn(&'a str) -> PResult<'a, T> {
+    move |s: &'a str| {
+        let f1 = match first.parse(s) {
+            Ok(r) => return Ok(r),
+            Err(f) => f,
+        };
+        let f2 = match second.parse(s) {
+            Ok(r) => return Ok(r),
+            Err(f) => f,
+        };
+        Err(match f1.at.len().cmp(&f2.at.len()) {
+            std::cmp::Ordering::Less => f1,
+            std::cmp::Ordering::Greater => f2,
+            std::cmp::Ordering::Equal => Failure::new(f1.at, format!("{} or {}", f1.expected, f2.expected)),
+        })
+    }
+}
+
+/// The value of `parser`, or `None` if it fails.
+pub fn opt<'a, T>(parser: impl Parser<'a, T>) -> impl Fn(&'a str) -> PResult<'a, Option<T>> {
+    move |s: &'a str| match parser.parse(s) {
+        Ok((value, rest)) => Ok((Some(value), rest)),
+        Err(_) => Ok((None, s)),
+    }
+}
+
+/// One or more `item`s separated by `separator`.
+pub fn sep_by1<'a, T, S>(item: impl Parser<'a, T>, separator: impl Parser<'a, S>) -> impl Fn(&'a str) -> PResult<'a, Vec<T>> {
+    move |s: &'a str| {
+        let (first, mut s) = item.parse(s)?;
+        let mut items = vec![first];
+        while let Ok((_, rest)) = separator.parse(s) {
+            let (next, rest) = item.parse(rest)?;
+            items.push(next);
+            s = rest;
+        }
+        Ok((items, s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grammar() {
+        let input = "move 3 from A, B\nmove -2 from C\nmove 7 to D\nmove x from A";
+        let lines = input.lines().collect::<Vec<_>>();
+        let grammar = || (
+            preceded(tag("move "), number::<i32>()),
+            preceded((ws(), tag("from"), ws()), sep_by1(ident(), tag(", "))),
+        );
+
+        assert_eq!(parse_line(input, lines[0], grammar()), Ok((3, vec!["A", "B"])));
+        assert_eq!(parse_line(input, lines[1], grammar()), Ok((-2, vec!["C"])));
+
+        let err = parse_line(input, lines[2], grammar()).unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (3, 8, "'from'"));
+        let err = parse_line(input, lines[3], grammar()).unwrap_err();
+        assert_eq!(err.to_string(), "line 4, column 6: expected a number, found 'x from A'");
+
+        let either = alt(tag("tunnels lead to valves "), tag("tunnel leads to valve "));
+        assert_eq!(either.parse("tunnel leads to valve AA"), Ok(("tunnel leads to valve ", "AA")));
+        assert_eq!(either.parse("tunnels").unwrap_err().expected, "'tunnels lead to valves ' or 'tunnel leads to valve '");
+        assert_eq!(opt(number::<u8>()).parse("256"), Ok((None, "256")));
+
+        let err = parse_line(input, &lines[0][..6], tag("move")).unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (5, "end of line"));
+    }
+}
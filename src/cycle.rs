@@ -0,0 +1,156 @@
+//! Cycle detection for simulations whose states eventually repeat, to know the outcome after a
+//! number of steps far too large to simulate.
+//!
+//! A simulation is a `state` changed in place by `step`, a `key` telling whether two states are the
+//! same (it can leave out anything that doesn't influence the following steps) and a `metric`
+//! recorded after every step, such as the height of a tower.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// The state after `start + period` steps is the same as after `start` steps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+/// Metrics of a simulation, from the initial state to the end of the first cycle if one was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<M> {
+    /// Metric after `i` steps at index `i`.
+    pub metrics: Vec<M>,
+    pub cycle: Option<Cycle>,
+}
+
+/// Why the metric after some number of steps isn't known.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The steps are past the simulated ones and no cycle was found.
+    NoCycle,
+    /// The extrapolated metric doesn't fit in its type.
+    Overflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoCycle => write!(f, "no cycle found in the simulated steps"),
+            Error::Overflow => write!(f, "the metric extrapolated over the cycles overflows"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl<M: Metric> History<M> {
+    /// Metric after `n` steps, assuming that it changes by the same amount during every period of the
+    /// cycle.
+    pub fn at(&self, n: usize) -> Result<M, Error> {
+        if let Some(m) = self.metrics.get(n) {
+            return Ok(m.clone());
+        }
+        let Cycle { start, period } = self.cycle.ok_or(Error::NoCycle)?;
+        let (cycles, offset) = ((n - start) / period, (n - start) % period);
+        let m = &self.metrics[start + offset];
+        m.advance(&self.metrics[start], &self.metrics[start + period], cycles).ok_or(Error::Overflow)
+    }
+}
+
+/// Steps `state` until its `key` repeats or `max_steps` steps were made, recording `metric` after
+/// every step (and before the first one).
+pub fn simulate<S, K, M>(
+    state: &mut S,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    mut metric: impl FnMut(&S) -> M,
+    max_steps: usize,
+) -> History<M>
+where
+    K: Eq + Hash,
+{
+    let mut seen = HashMap::from([(key(state), 0)]);
+    let mut metrics = vec![metric(state)];
+    for i in 1..=max_steps {
+        step(state);
+        metrics.push(metric(state));
+        if let Some(start) = seen.insert(key(state), i) {
+            return History { metrics, cycle: Some(Cycle { start, period: i - start }) };
+        }
+    }
+    History { metrics, cycle: None }
+}
+
+/// Metric after `n` steps of the simulation, only simulating until the first cycle.
+///
+/// Panics if the extrapolated metric overflows.
+pub fn metric_after<S, K, M>(
+    mut state: S,
+    step: impl FnMut(&mut S),
+    key: impl FnMut(&S) -> K,
+    metric: impl FnMut(&S) -> M,
+    n: usize,
+) -> M
+where
+    K: Eq + Hash,
+    M: Metric,
+{
+    simulate(&mut state, step, key, metric, n).at(n).unwrap_or_else(|err| panic!("metric after {n} steps: {err}"))
+}
+
+/// A metric that can be extrapolated over several periods of a cycle.
+pub trait Metric: Clone {
+    /// `self` changed `cycles` times by the change from `from` to `to`, which can be negative. `None`
+    /// if the result doesn't fit in `Self`.
+    fn advance(&self, from: &Self, to: &Self, cycles: usize) -> Option<Self>;
+}
+
+macro_rules! impl_metric {
+    ($($t:ty),*) => {
+        $(
+            impl Metric for $t {
+                fn advance(&self, from: &Self, to: &Self, cycles: usize) -> Option<Self> {
+                    let delta = (*to as i128 - *from as i128).checked_mul(cycles as i128)?;
+                    <$t>::try_from((*self as i128).checked_add(delta)?).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_metric!(i32, i64, u32, u64, usize);
+
+/// Each element extrapolated on its own.
+impl<T: Metric> Metric for Vec<T> {
+    fn advance(&self, from: &Self, to: &Self, cycles: usize) -> Option<Self> {
+        self.iter().zip(from).zip(to).map(|((m, from), to)| m.advance(from, to, cycles)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle() {
+        // the state goes 0, 1, 2, 3, 4, 2, 3, 4, ... while the metric gains 10 per step
+        let next = |n: &mut (u32, u64)| *n = (if n.0 == 4 { 2 } else { n.0 + 1 }, n.1 + 10);
+        let history = simulate(&mut (0, 0), next, |n| n.0, |n| n.1, 100);
+        assert_eq!(history.cycle, Some(Cycle { start: 2, period: 3 }));
+        assert_eq!(history.metrics, [0, 10, 20, 30, 40, 50]);
+        assert_eq!(history.at(4), Ok(40));
+        assert_eq!(history.at(1_000_000_000_000), Ok(10_000_000_000_000));
+        assert_eq!(metric_after((0, 0), next, |n| n.0, |n| vec![n.1, 1], 7), [70, 1]);
+
+        let history = simulate(&mut 0u64, |n| *n += 1, |n| *n, |n| *n, 3);
+        assert_eq!((history.cycle, history.at(3), history.at(4)), (None, Ok(3), Err(Error::NoCycle)));
+
+        // a metric shrinking over the cycle, then one growing past its type
+        let next = |n: &mut (u32, u64)| *n = ((n.0 + 1) % 2, n.1 - 1);
+        assert_eq!(metric_after((0, 100), next, |n| n.0, |n| n.1, 50), 50);
+        let history = simulate(&mut (0, 0), |n| *n = ((n.0 + 1) % 2, n.1 + 1), |n| n.0, |n| n.1 as u32, 10);
+        assert_eq!(history.at(u32::MAX as usize), Ok(u32::MAX));
+        assert_eq!(history.at(u32::MAX as usize + 1), Err(Error::Overflow));
+    }
+}
@@ -1,38 +1,86 @@
 use std::cmp::max;
-mod profiler;
 
-fn main() {
-    let _p = profiler::profile();
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
-    let input = include_str!("data/input1");
-    let res = input
+/// Calories of each line, `None` for the blank lines between elves.
+pub fn parse(input: &str) -> Result<Vec<Option<u32>>, ParseError> {
+    input
         .lines()
-        .map(|l| if l.len() > 0 { Some(l.parse::<u32>().unwrap()) } else { None })
-        .fold((0u32, 0u32, [0u32; 3]), |mut acc, n| {
-            // acc.0 = current group sum
-            // acc.1 = max sum (part 1)
-            // acc.2 = stack of top 3 max sums (part 2)
-            if let Some(num) = n {
-                acc.0 += num;
-            } else {
-                acc.1 = max(acc.0, acc.1);
-
-                if acc.1 > acc.2[0] { // try push new max sum into top 3 stack
-                    if acc.2[0] > acc.2[1] {
-                        if acc.2[1] > acc.2[2] {
-                            acc.2[2] = acc.2[1];
-                        }
+        .map(|l| if !l.is_empty() { parser::number(input, l).map(Some) } else { Ok(None) })
+        .collect()
+}
+
+/// Calories of the elf carrying the most and of the top 3, in descending order.
+fn count(lines: &[Option<u32>]) -> (u32, [u32; 3]) {
+    // the last elf isn't followed by a blank line
+    let res = lines.iter().copied().chain([None]).fold((0u32, 0u32, [0u32; 3]), |mut acc, n| {
+        // acc.0 = current group sum
+        // acc.1 = max sum (part 1)
+        // acc.2 = stack of top 3 max sums (part 2)
+        if let Some(num) = n {
+            acc.0 += num;
+        } else {
+            acc.1 = max(acc.0, acc.1);
+
+            if acc.0 > acc.2[2] { // try push the group sum into the top 3 stack
+                if acc.0 > acc.2[1] {
+                    acc.2[2] = acc.2[1];
+                    if acc.0 > acc.2[0] {
                         acc.2[1] = acc.2[0];
+                        acc.2[0] = acc.0;
+                    } else {
+                        acc.2[1] = acc.0;
                     }
-                    acc.2[0] = acc.1;
+                } else {
+                    acc.2[2] = acc.0;
                 }
-
-                acc.0 = 0; // reset sum
             }
-            acc
-        });
-    let part1 = res.1;
-    let part2 = res.2.iter().copied().reduce(|acc, n| acc + n).unwrap();
-    println!("[Part 1] Result is {part1}");
-    println!("[Part 2] Result is {part2} ({:?})", res.2);
+
+            acc.0 = 0; // reset sum
+        }
+        acc
+    });
+    (res.1, res.2)
+}
+
+pub fn part1(lines: &[Option<u32>]) -> u32 {
+    count(lines).0
+}
+
+pub fn part2(lines: &[Option<u32>]) -> u32 {
+    count(lines).1.iter().copied().reduce(|acc, n| acc + n).unwrap()
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    type Input = Vec<Option<u32>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example1");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 24000);
+        assert_eq!(part2(&input), 45000);
+    }
 }
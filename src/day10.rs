@@ -1,50 +1,100 @@
-mod profiler;
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
 #[derive(Debug, Copy, Clone)]
-struct CPU {
+pub enum Instruction {
+    Noop,
+    Addx(i64),
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Cpu {
     cycle: i64,
     x: i64,
 }
 
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input10");
-
-    let mut cpu = CPU { cycle: 1, x: 1 };
-    let mut screen = [['.'; 40]; 6];
-    let res1 = input
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    input
         .lines()
-        .flat_map(|l| match &l[..4] {
-            "noop" => vec![
-                { cpu = CPU { cycle: cpu.cycle + 1, ..cpu }; cpu },
+        .map(|l| match l.split_once(' ') {
+            None if l == "noop" => Ok(Instruction::Noop),
+            Some(("addx", n)) => Ok(Instruction::Addx(parser::number(input, n)?)),
+            _ => Err(ParseError::at(input, l, "'noop' or 'addx <n>'")),
+        })
+        .collect()
+}
+
+/// CPU state at the end of each cycle.
+fn execute(program: &[Instruction]) -> impl Iterator<Item = Cpu> + '_ {
+    let mut cpu = Cpu { cycle: 1, x: 1 };
+    program.iter()
+        .flat_map(move |instr| match instr {
+            Instruction::Noop => vec![
+                { cpu = Cpu { cycle: cpu.cycle + 1, ..cpu }; cpu },
             ],
-            "addx" => vec![
-                { cpu = CPU { cycle: cpu.cycle + 1, ..cpu }; cpu },
-                { cpu = CPU { cycle: cpu.cycle + 1, x: cpu.x + l[5..].parse::<i64>().unwrap() }; cpu },
+            Instruction::Addx(n) => vec![
+                { cpu = Cpu { cycle: cpu.cycle + 1, ..cpu }; cpu },
+                { cpu = Cpu { cycle: cpu.cycle + 1, x: cpu.x + n }; cpu },
             ],
-            _=> panic!("unknown instruction"),
-        })
-        .map(|cpu| {
-            // part 1
-            let signal_strength = if (cpu.cycle - 20) % 40 == 0 { cpu.cycle * cpu.x } else { 0 };
-
-            // part 2
-            {
-                // not perfect, we skip the first cycle so the top-left corner is always '.'
-                let pixel_x = (cpu.cycle - 1) % 40;
-                let pixel_y = (cpu.cycle - 1) / 40;
-                if pixel_x >= cpu.x - 1 && pixel_x <= cpu.x + 1 {
-                    screen[pixel_y as usize][pixel_x as usize] = '#';
-                }
-            }
-
-            signal_strength
         })
-        .sum::<i64>();
-    println!("[Part 1] Result is {res1}");
-    println!("[Part 2] Result is:");
-    for line in screen {
-        println!("\t{}", line.iter().collect::<String>());
+}
+
+pub fn part1(program: &[Instruction]) -> i64 {
+    execute(program)
+        .map(|cpu| if (cpu.cycle - 20) % 40 == 0 { cpu.cycle * cpu.x } else { 0 })
+        .sum::<i64>()
+}
+
+/// Rows of the CRT screen, lit pixels are `#`.
+pub fn part2(program: &[Instruction]) -> Vec<String> {
+    let mut screen = [['.'; 40]; 6];
+    for cpu in execute(program) {
+        // not perfect, we skip the first cycle so the top-left corner is always '.'
+        let pixel_x = (cpu.cycle - 1) % 40;
+        let pixel_y = (cpu.cycle - 1) / 40;
+        if pixel_x >= cpu.x - 1 && pixel_x <= cpu.x + 1 {
+            screen[pixel_y as usize][pixel_x as usize] = '#';
+        }
+    }
+    screen.iter().map(|line| line.iter().collect()).collect()
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        Answer::Screen(part2(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example10");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 13140);
+        assert_eq!(part2(&input), [
+            ".#..##..##..##..##..##..##..##..##..##..",
+            "###...###...###...###...###...###...###.",
+            "####....####....####....####....####....",
+            "#####.....#####.....#####.....#####.....",
+            "######......######......######......####",
+            "#######.......#######.......#######.....",
+        ]);
     }
 }
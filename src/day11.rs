@@ -1,6 +1,8 @@
 use std::collections::VecDeque;
 
-mod profiler;
+use crate::{checked, cycle};
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
 #[derive(Debug, Copy, Clone)]
 enum Operand {
@@ -9,12 +11,12 @@ enum Operand {
 }
 
 impl Operand {
-    fn parse(def: &str) -> Option<Self> {
+    fn parse(input: &str, def: &str) -> Result<Self, ParseError> {
         use Operand::*;
 
-        Some(match def {
+        Ok(match def {
             "old" => Old,
-            int => Int(int.parse::<u64>().ok()?),
+            int => Int(int.parse::<u64>().map_err(|_| ParseError::at(input, def, "'old' or a number"))?),
         })
     }
 
@@ -35,17 +37,17 @@ enum Operation {
 }
 
 impl Operation {
-    fn parse(def: &str) -> Option<Self> {
+    fn parse(input: &str, def: &str) -> Result<Self, ParseError> {
         use Operation::*;
 
-        let mut parts = def.split(' ');
-        let operand1 = parts.next().and_then(Operand::parse)?;
-        let op = parts.next()?;
-        let operand2 = parts.next().and_then(Operand::parse)?;
-        Some(match op {
+        let (operand1, def_rest) = parser::split_once(input, def, " ")?;
+        let (op, operand2) = parser::split_once(input, def_rest, " ")?;
+        let operand1 = Operand::parse(input, operand1)?;
+        let operand2 = Operand::parse(input, operand2)?;
+        Ok(match op {
             "+" => Add(operand1, operand2),
             "*" => Mul(operand1, operand2),
-            _ => None?,
+            _ => return Err(ParseError::at(input, op, "'+' or '*'")),
         })
     }
 
@@ -53,8 +55,8 @@ impl Operation {
         use Operation::*;
 
         match self {
-            Add(o1, o2) => o1.value(old) + o2.value(old),
-            Mul(o1, o2) => o1.value(old) * o2.value(old),
+            Add(o1, o2) => checked::add(Day11::DAY, o1.value(old), o2.value(old)),
+            Mul(o1, o2) => checked::mul(Day11::DAY, o1.value(old), o2.value(old)),
         }
     }
 
@@ -62,14 +64,14 @@ impl Operation {
         use Operation::*;
 
         (match self {
-            Add(o1, o2) => (o1.value(old) % mod_value) + (o2.value(old) % mod_value),
-            Mul(o1, o2) => (o1.value(old) % mod_value) * (o2.value(old) % mod_value),
+            Add(o1, o2) => checked::add(Day11::DAY, o1.value(old) % mod_value, o2.value(old) % mod_value),
+            Mul(o1, o2) => checked::mul(Day11::DAY, o1.value(old) % mod_value, o2.value(old) % mod_value),
         }) % mod_value
     }
 }
 
 #[derive(Debug, Clone)]
-struct Monkey {
+pub struct Monkey {
     items: VecDeque<u64>,
     operation: Operation,
     divisible_test: u64,
@@ -79,34 +81,36 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn parse(def: &str) -> Option<Self> {
+    fn parse(input: &str, def: &str) -> Result<Self, ParseError> {
         let mut lines = def.lines();
-        lines.next(); // skip 'Monkey N:'
-        let items = lines.next()
-            .and_then(
-                |s| s.split_once(": ").and_then(
-                    |s| s.1.split(", ").map(|s| s.parse::<u64>().ok()).collect::<Option<VecDeque<_>>>()
-                )
-            )?;
-
-        let operation = lines.next()
-            .and_then(
-                |s| s.split_once("new = ").and_then(
-                    |s| Operation::parse(s.1)
-                )
-            )?;
-
-        fn parse_last_int<T: std::str::FromStr>(s: &str) -> Option<T> {
-            s.rsplit_once(" ").and_then(
-                |s| s.1.parse::<T>().ok()
-            )
+        let mut next_line = |expected: &str| {
+            lines.next().ok_or_else(|| ParseError::after(input, def, expected.to_string()))
+        };
+
+        parser::strip_prefix(input, next_line("'Monkey N:'")?, "Monkey ")?;
+        let items = parser::split_once(input, next_line("'Starting items: ...'")?, "Starting items: ")?.1;
+        let items = if items.is_empty() {
+            VecDeque::new()
+        } else {
+            items.split(", ").map(|s| parser::number::<u64>(input, s)).collect::<Result<VecDeque<_>, _>>()?
+        };
+
+        let operation = parser::split_once(input, next_line("'Operation: ...'")?, "new = ")?.1;
+        let operation = Operation::parse(input, operation)?;
+
+        fn parse_last_int<T: std::str::FromStr>(input: &str, s: &str) -> Result<T, ParseError> {
+            parser::number(input, s.rsplit_once(' ').map_or(s, |s| s.1))
         }
 
-        let divisible_test = lines.next().and_then(parse_last_int::<u64>)?;
-        let if_true = lines.next().and_then(parse_last_int::<usize>)?;
-        let if_false = lines.next().and_then(parse_last_int::<usize>)?;
+        let test = next_line("'Test: ...'")?;
+        let divisible_test = parse_last_int::<u64>(input, test)?;
+        if divisible_test == 0 {
+            return Err(ParseError::at(input, test.rsplit_once(' ').map_or(test, |s| s.1), "a divisor greater than 0"));
+        }
+        let if_true = parse_last_int::<usize>(input, next_line("'If true: ...'")?)?;
+        let if_false = parse_last_int::<usize>(input, next_line("'If false: ...'")?)?;
 
-        Some(Self {
+        Ok(Self {
             items,
             operation,
             divisible_test,
@@ -117,70 +121,132 @@ impl Monkey {
     }
 }
 
-fn resolve<const PART: usize>(monkeys: &mut Vec<Monkey>) -> usize {
+fn play_round<const PART: usize>(monkeys: &mut [Monkey], lcm: u64) {
+    for monkey_index in 0..monkeys.len() {
+        let (first, second) = monkeys.split_at_mut(monkey_index);
+        let (monkey_slice, third) = second.split_at_mut(1);
+        let monkey = &mut monkey_slice[0];
+        while let Some(item_worry) = monkey.items.pop_front() {
+            monkey.num_inspected_items += 1;
+
+            let new_item_worry = if PART == 1 {
+                monkey.operation.compute(item_worry) / 3
+            } else {
+                monkey.operation.compute_mod(item_worry, lcm)
+            };
+
+            let target_monkey = if new_item_worry % monkey.divisible_test == 0 {
+                monkey.if_true
+            } else {
+                monkey.if_false
+            };
+
+            if target_monkey < first.len() {
+                &mut first[target_monkey]
+            } else {
+                &mut third[target_monkey - 1 - first.len()]
+            }.items.push_back(new_item_worry);
+        }
+    }
+}
+
+fn resolve<const PART: usize>(monkeys: Vec<Monkey>) -> usize {
 
     // The 'divisible test' values are unique prime numbers, so compute the least-common-multiple to wrap
     // around the operations using modulus for part 2
-    let lcm = monkeys.iter().fold(1, |acc, m| acc * m.divisible_test);
+    let lcm = monkeys.iter().fold(1, |acc, m| checked::mul(Day11::DAY, acc, m.divisible_test));
 
+    // the items held by each monkey are all that matter for the next rounds, the inspection counts
+    // only grow by the same amounts once they repeat
     let num_rounds: usize = if PART == 1 { 20 } else { 10_000 };
-    for round in 1..=num_rounds {
-        for monkey_index in 0..monkeys.len() {
-            let (first, second) = monkeys.split_at_mut(monkey_index);
-            let (monkey_slice, third) = second.split_at_mut(1);
-            let monkey = &mut monkey_slice[0];
-            while let Some(item_worry) = monkey.items.pop_front() {
-                monkey.num_inspected_items += 1;
-
-                let new_item_worry = if PART == 1 {
-                    monkey.operation.compute(item_worry) / 3
-                } else {
-                    monkey.operation.compute_mod(item_worry, lcm)
-                };
-
-                let target_monkey = if new_item_worry % monkey.divisible_test == 0 {
-                    monkey.if_true
-                } else {
-                    monkey.if_false
-                };
-
-                if target_monkey < first.len() {
-                    &mut first[target_monkey]
-                } else {
-                    &mut third[target_monkey - 1 - first.len()]
-                }.items.push_back(new_item_worry);
+    let mut round = 0;
+    let step = |monkeys: &mut Vec<Monkey>| {
+        play_round::<PART>(monkeys, lcm);
+        round += 1;
+        if round == 1 || round == 20 || round % 1000 == 0 {
+            crate::trace!("== After round {round} ==");
+            for (monkey_index, monkey) in monkeys.iter().enumerate() {
+                crate::trace!("Monkey {monkey_index} inspected items {} times.", monkey.num_inspected_items);
             }
+            crate::trace!();
         }
+    };
+    let items = |monkeys: &Vec<Monkey>| monkeys.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
+    let counts = |monkeys: &Vec<Monkey>| monkeys.iter().map(|m| m.num_inspected_items).collect::<Vec<_>>();
+    let mut counts = cycle::metric_after(monkeys, step, items, counts, num_rounds);
+
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    crate::debug!("{} * {} = {}", counts[0], counts[1], counts[0] * counts[1]);
+    checked::mul(Day11::DAY, counts[0], counts[1])
+}
 
-        if round == 1 || round == 20 || round % 1000 == 0 {
-            println!("== After round {round} ==");
-            for monkey_index in 0..monkeys.len() {
-                println!("Monkey {monkey_index} inspected items {} times.", monkeys[monkey_index].num_inspected_items);
-            }
-            println!();
+pub fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let monkeys = input
+        .split("\n\n")
+        .map(|def| Monkey::parse(input, def))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the monkey business is the product of the two most active monkeys
+    if monkeys.len() < 2 {
+        return Err(ParseError::after(input, input, "at least two monkeys"));
+    }
+    for (def, monkey) in input.split("\n\n").zip(&monkeys) {
+        if monkey.if_true >= monkeys.len() || monkey.if_false >= monkeys.len() {
+            return Err(ParseError::at(input, def, format!("a monkey throwing to monkeys 0 to {}", monkeys.len() - 1)));
         }
     }
 
-    monkeys.sort_unstable_by_key(|m| usize::MAX - m.num_inspected_items);
-    println!("{} * {} = {}", monkeys[0].num_inspected_items, monkeys[1].num_inspected_items, monkeys[0].num_inspected_items * monkeys[1].num_inspected_items);
-    monkeys[0].num_inspected_items * monkeys[1].num_inspected_items
+    Ok(monkeys)
+}
+
+pub fn part1(monkeys: &[Monkey]) -> usize {
+    resolve::<1>(monkeys.to_vec())
+}
+
+pub fn part2(monkeys: &[Monkey]) -> usize {
+    resolve::<2>(monkeys.to_vec())
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = Vec<Monkey>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
-fn main() {
-    let _p = profiler::profile();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let input = include_str!("data/input11");
+    const EXAMPLE: &str = include_str!("data/example11");
 
-    let mut monkeys = input
-        .split("\n\n")
-        .map(Monkey::parse)
-        .map(|m| m.unwrap())
-        .collect::<Vec<_>>();
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 10605);
+        assert_eq!(part2(&input), 2713310158);
+    }
 
-    let mut monkeys2 = monkeys.clone();
+    #[test]
+    fn test_parse_errors() {
+        let monkeys = EXAMPLE.split("\n\n").collect::<Vec<_>>();
+        let err = parse(monkeys[0]).unwrap_err();
+        assert_eq!(err.expected, "at least two monkeys");
 
-    let res1 = resolve::<1>(&mut monkeys);
-    let res2 = resolve::<2>(&mut monkeys2);
-    println!("[Part 1] Result is {res1}");
-    println!("[Part 2] Result is {res2}");
+        let input = EXAMPLE.replace("divisible by 13", "divisible by 0");
+        let err = parse(&input).unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (18, 22, "a divisor greater than 0"));
+    }
 }
@@ -1,66 +1,90 @@
-use std::collections::VecDeque;
+use crate::geom::Vec2;
+use crate::grid::Grid;
+use crate::parser::ParseError;
+use crate::{parallel, search};
+use crate::{Answer, Solution};
 
-mod profiler;
+/// Fewest steps from `start` to `end`, climbing at most one level per step.
+fn shortest_path(grid: &Grid<u8>, start: Vec2<i64>, end: Vec2<i64>) -> Option<usize> {
+    let successors = |&p: &Vec2<i64>| grid.neighbours4(p).filter(move |&np| grid[np] <= grid[p] + 1);
+    let mut reached = Grid::new(grid.width(), grid.height(), false).with_origin(grid.origin());
+    search::bfs_cost_by(start, successors, |&p| p == end, |&p| !std::mem::replace(&mut reached[p], true)).cost
+}
+
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    grid: Grid<u8>,
+    start: Vec2<i64>,
+    end: Vec2<i64>,
+}
 
-type Grid = Vec<Vec<u8>>;
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Pos { x: usize, y: usize }
-fn pos(x: usize, y: usize) -> Pos { Pos { x, y } }
+pub fn parse(input: &str) -> Result<Heightmap, ParseError> {
+    let width = input.lines().next().map_or(0, str::len);
+    if let Some(l) = input.lines().find(|l| l.len() != width) {
+        return Err(ParseError::at(input, l, format!("a row of {width} squares")));
+    }
 
-fn pathfind_bfs(grid: &Grid, start: Pos, end: Pos) -> Option<usize> {
-    type Step = (Pos, usize);
+    let (mut has_start, mut has_end) = (false, false);
+    let mut grid = Grid::parse(input, input, "an elevation (a-z), or a single 'S' and 'E'", |n| match n {
+        b'S' if !has_start => { has_start = true; Some(n) },
+        b'E' if !has_end => { has_end = true; Some(n) },
+        b'a'..=b'z' => Some(n),
+        _ => None,
+    })?;
 
-    let height = grid.len();
-    let width = grid[0].len();
-    let mut visited_grid: Vec<Vec<bool>> = vec![vec![false; width]; height];
-    let mut queue = VecDeque::<Step>::new();
-    visited_grid[start.y][start.x] = true;
-    queue.push_back((start, 0));
-    while let Some((curr, number_of_steps)) = queue.pop_front() {
-        if curr == end {
-            return Some(number_of_steps);
-        }
-        for np in [
-            if curr.x > 0 { Some(pos(curr.x - 1, curr.y)) } else { None }, // left
-            if curr.y > 0 { Some(pos(curr.x, curr.y - 1)) } else { None }, // up
-            if curr.x < width - 1 { Some(pos(curr.x + 1, curr.y)) } else { None }, // right
-            if curr.y < height - 1 { Some(pos(curr.x, curr.y + 1)) } else { None }, // down
-        ]  {
-            let (nx, ny) = if let Some(Pos{x: nx, y: ny}) = np { (nx, ny) } else { continue; };
-            if !visited_grid[ny][nx] && grid[ny][nx] <= (grid[curr.y][curr.x] + 1) {
-                visited_grid[ny][nx] = true;
-                // println!("({}, {}) -> ({}, {})   (steps: {})", curr.x, curr.y, nx, ny, number_of_steps + 1);
-                queue.push_back((pos(nx, ny), number_of_steps + 1));
-            }
-        };
+    match (grid.position(|&n| n == b'S'), grid.position(|&n| n == b'E')) {
+        (Some(start), Some(end)) => {
+            grid[start] = b'a';
+            grid[end] = b'z';
+            Ok(Heightmap { grid, start, end })
+        },
+        _ => Err(ParseError::after(input, input, "both the start 'S' and the end 'E'")),
     }
+}
+
+pub fn part1(map: &Heightmap) -> Option<usize> {
+    shortest_path(&map.grid, map.start, map.end)
+}
 
-    return None;
+pub fn part2(map: &Heightmap) -> Option<usize> {
+    let starts = map.grid.cells()
+        .filter_map(|(p, n)| if *n == b'a' { Some(p) } else { None })
+        .collect::<Vec<_>>();
+    parallel::map(&starts, |&new_start| shortest_path(&map.grid, new_start, map.end))
+        .into_iter()
+        .flatten()
+        .min()
 }
 
-fn main() {
-    let _p = profiler::profile();
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Heightmap;
 
-    let input = include_str!("data/input12");
-    let mut start = pos(0, 0);
-    let mut end = pos(0, 0);
-    let grid: Grid = input
-        .lines().enumerate()
-        .map(|(y, l)| l.bytes().enumerate().map(|(x, n)| match n {
-            b'S' => { start = pos(x, y); b'a' },
-            b'E' => { end = pos(x, y); b'z' },
-            _ => n,
-        }).collect::<Vec<_>>()
-        ).collect::<Vec<_>>();
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
 
-    let res1 = pathfind_bfs(&grid, start, end);
-    println!("[Part 1] Result is {res1:?}");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let res2 = grid.iter().enumerate()
-        .flat_map(|(y, row)| row.iter().enumerate()
-            .filter_map(move |(x, n)| if *n == b'a' { Some(pos(x, y)) } else { None })
-        ).map(|new_start| pathfind_bfs(&grid, new_start, end))
-        .filter_map(|num_steps| num_steps)
-        .min();
-    println!("[Part 2] Result is {res2:?}");
+    const EXAMPLE: &str = include_str!("data/example12");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), Some(31));
+        assert_eq!(part2(&input), Some(29));
+    }
 }
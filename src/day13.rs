@@ -1,50 +1,65 @@
 use std::cmp::Ordering;
 
-mod profiler;
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-enum Value {
+pub enum Value {
     Int(u64),
     List(List),
 }
 type List = Vec<Value>;
 
-fn parse_int(s: &[u8], i: &mut usize) -> Value {
+// `input` is the whole puzzle input, used to locate errors in `s`
+fn parse_int(input: &str, s: &str, i: &mut usize) -> Result<Value, ParseError> {
     let start = *i;
     *i += 1;
-    while (b'0'..=b'9').contains(&s[*i]) { *i += 1; }
+    while s.as_bytes().get(*i).is_some_and(u8::is_ascii_digit) { *i += 1; }
 
     let end = *i;
-    Value::Int(std::str::from_utf8(&s[start..end]).unwrap().parse::<u64>().unwrap())
+    Ok(Value::Int(parser::number(input, &s[start..end])?))
 }
 
-fn parse_list(s: &[u8], i: &mut usize) -> Value {
-    assert_eq!(b'[', s[*i]);
+fn parse_list(input: &str, s: &str, i: &mut usize) -> Result<Value, ParseError> {
+    let expect = |i: usize, c: u8| if s.as_bytes().get(i) == Some(&c) {
+        Ok(())
+    } else {
+        Err(ParseError::at(input, &s[i..], format!("'{}'", c as char)))
+    };
+
+    expect(*i, b'[')?;
     *i += 1;
 
     let mut res = List::new();
     loop {
-        match s[*i] {
-            b'0'..=b'9' => res.push(parse_int(s, i)),
-            b'[' => res.push(parse_list(s, i)),
-            b']' => (),
-            c => panic!("unknown format: '{}' (index: {})",
-                        std::char::from_u32(c as u32).unwrap(),
-                        *i),
+        match s.as_bytes().get(*i) {
+            Some(b'0'..=b'9') => res.push(parse_int(input, s, i)?),
+            Some(b'[') => res.push(parse_list(input, s, i)?),
+            Some(b']') => (),
+            _ => return Err(ParseError::at(input, &s[*i..], "a number, '[' or ']'")),
         };
 
-        if s[*i] == b']' {
+        if s.as_bytes().get(*i) == Some(&b']') {
             break;
         } else {
-            assert_eq!(b',', s[*i]);
+            expect(*i, b',')?;
             *i += 1;
         }
     }
 
-    assert_eq!(b']', s[*i]);
+    expect(*i, b']')?;
     *i += 1;
 
-    Value::List(res)
+    Ok(Value::List(res))
+}
+
+fn parse_packet(input: &str, s: &str) -> Result<Value, ParseError> {
+    let mut i = 0;
+    let packet = parse_list(input, s, &mut i)?;
+    if i < s.len() {
+        return Err(ParseError::at(input, &s[i..], "end of packet"));
+    }
+    Ok(packet)
 }
 
 fn cmp(a: &Value, b: &Value) -> Ordering {
@@ -67,32 +82,32 @@ fn cmp(a: &Value, b: &Value) -> Ordering {
     }
 }
 
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input13");
-    let pairs = input
+pub fn parse(input: &str) -> Result<Vec<(Value, Value)>, ParseError> {
+    input
         .split("\n\n")
         .map(|pair_str| {
-            let (a, b) = pair_str.split_once('\n').unwrap();
-            (parse_list(a.as_bytes(), &mut 0), parse_list(b.as_bytes(), &mut 0))
+            let (a, b) = parser::split_once(input, pair_str, "\n")?;
+            Ok((parse_packet(input, a)?, parse_packet(input, b.trim_end())?))
         })
-        .collect::<Vec<_>>();
-
+        .collect()
+}
 
-    let res1 = pairs.iter()
+pub fn part1(pairs: &[(Value, Value)]) -> usize {
+    pairs.iter()
         .enumerate()
-        .inspect(|(i, (a, b))| println!("#{}: {:?} ({})", i + 1, cmp(a, b), if cmp(a, b) == Ordering::Less { "correct" } else { "incorrect" }))
+        .inspect(|(i, (a, b))| crate::trace!("#{}: {:?} ({})", i + 1, cmp(a, b), if cmp(a, b) == Ordering::Less { "correct" } else { "incorrect" }))
         .filter(|(_, (a, b))| cmp(a, b) == Ordering::Less)
         .map(|(i, _)| i + 1)
-        .sum::<usize>();
-    println!("[Part 1] Result is {res1}");
+        .sum::<usize>()
+}
 
-    let divider1 = parse_list("[[2]]".as_bytes(), &mut 0);
-    let divider2 = parse_list("[[6]]".as_bytes(), &mut 0);
+pub fn part2(pairs: &[(Value, Value)]) -> usize {
+    let divider = |s| parse_packet(s, s).unwrap();
+    let divider1 = divider("[[2]]");
+    let divider2 = divider("[[6]]");
 
-    let mut packets = pairs.into_iter()
-        .flat_map(|(a, b)| [a, b])
+    let mut packets = pairs.iter()
+        .flat_map(|(a, b)| [a.clone(), b.clone()])
         .chain([
             divider1.clone(),
             divider2.clone(),
@@ -100,9 +115,74 @@ fn main() {
         .collect::<Vec<_>>();
     packets.sort_unstable_by(cmp);
 
-    let res2 = packets.iter()
+    packets.iter()
         .enumerate()
         .filter_map(|(i, v)| if v == &divider1 || v == &divider2 { Some(i + 1) } else { None })
-        .product::<usize>();
-    println!("[Part 2] Result is {res2}");
+        .product::<usize>()
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input = Vec<(Value, Value)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example13");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 13);
+        assert_eq!(part2(&input), 140);
+    }
+
+    #[test]
+    fn test_cmp() {
+        let input = parse(EXAMPLE).unwrap();
+        let orders = input.iter().map(|(a, b)| cmp(a, b)).collect::<Vec<_>>();
+        use Ordering::*;
+        assert_eq!(orders, [Less, Less, Greater, Less, Greater, Less, Greater, Greater]);
+
+        let packet = |s| parse_packet(s, s).unwrap();
+        assert_eq!(cmp(&packet("[[1]]"), &packet("[1]")), Equal);
+        assert_eq!(cmp(&packet("[[]]"), &packet("[[],1]")), Less);
+        assert_eq!(cmp(&Value::Int(3), &packet("[[3],[4]]")), Less);
+    }
+
+    #[test]
+    fn test_cmp_edge_cases() {
+        use Ordering::*;
+        let packet = |s| parse_packet(s, s).unwrap();
+        let cases = [
+            ("[]", "[]", Equal),
+            ("[]", "[[]]", Less),
+            ("[[[]]]", "[[]]", Greater),
+            ("[10]", "[9]", Greater),
+            ("[1,[2]]", "[1,2]", Equal),
+            ("[[1],2]", "[1,3]", Less),
+            ("[1,1]", "[1]", Greater),
+            ("[[]]", "[0]", Less),
+        ];
+        for (a, b, order) in cases {
+            assert_eq!(cmp(&packet(a), &packet(b)), order, "{a} vs {b}");
+            assert_eq!(cmp(&packet(b), &packet(a)), order.reverse(), "{b} vs {a}");
+        }
+    }
 }
@@ -1,82 +1,100 @@
 use std::collections::HashSet;
 
-mod profiler;
+use crate::geom::{vec2, Bounds, Vec2};
+use crate::grid::Grid;
+use crate::image::{self, Image, Rgb};
+use crate::parser::{self, ParseError};
+use crate::visualize;
+use crate::{Answer, Solution};
 
 #[derive(Debug, Clone)]
-struct Grid {
-    min: Pos,
-    max: Pos,
-    grid: Vec<Vec<u8>>,
-    extra_floor: HashSet<Pos>, // for part 2
+pub struct Cave {
+    grid: Grid<u8>,
+    extra_floor: HashSet<Vec2<i64>>, // for part 2
 }
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Pos { x: usize, y: usize }
-const fn pos(x: usize, y: usize) -> Pos { Pos { x, y } }
 
 const AIR: u8 = b'.';
 const ROCK: u8 = b'#';
 const SAND: u8 = b'o';
 
-const SAND_SOURCE: Pos = pos(500, 0);
-
-fn build_grid(lines: &Vec<Vec<Pos>>) -> Grid {
-    let (min_x, max_x) = lines.iter()
-        .flat_map(|l| l.iter().map(|p| p.x))
-        .chain([SAND_SOURCE.x])
-        .fold((usize::MAX, usize::MIN), |acc, n| (acc.0.min(n), acc.1.max(n)));
-    let (min_y, max_y) = lines.iter()
-        .flat_map(|l| l.iter().map(|p| p.y))
-        .chain([SAND_SOURCE.y])
-        .fold((usize::MAX, usize::MIN), |acc, n| (acc.0.min(n), acc.1.max(n)));
-
-    println!("Min: ({}, {})", min_x, min_y);
-    println!("Max: ({}, {})", max_x, max_y);
-    let width = max_x - min_x + 1;
-    let height = max_y - min_y + 1;
-    println!("Width×Height: {}×{}", width, height);
-    let mut grid = vec![vec![AIR; width]; height];
+const SAND_SOURCE: Vec2<i64> = vec2(500, 0);
+
+fn build_cave(lines: &Vec<Vec<Vec2<i64>>>) -> Cave {
+    let bounds = Bounds::from_points(lines.iter().flatten().copied().chain([SAND_SOURCE])).unwrap();
+    let size = bounds.size();
+
+    crate::debug!("Min: {}", bounds.min);
+    crate::debug!("Max: {}", bounds.max);
+    crate::debug!("Width×Height: {}×{}", size.x, size.y);
+    let mut grid = Grid::new(size.x as usize, size.y as usize, AIR).with_origin(bounds.min);
 
     for line in lines {
         for w in line.windows(2) {
             let a = w[0];
             let b = w[1];
             for x in a.x.min(b.x)..=a.x.max(b.x) {
-                grid[a.y - min_y][x - min_x] = ROCK;
+                grid[vec2(x, a.y)] = ROCK;
             }
             for y in a.y.min(b.y)..=a.y.max(b.y) {
-                grid[y - min_y][a.x - min_x] = ROCK;
+                grid[vec2(a.x, y)] = ROCK;
             }
         }
     }
 
-    Grid {
-        min: pos(min_x, min_y),
-        max: pos(max_x, max_y),
+    Cave {
         grid,
         extra_floor: HashSet::new(),
     }
 }
 
-fn print_grid(g: &Grid) {
-    for row in &g.grid {
-        println!("{}", std::str::from_utf8(&row).unwrap());
+/// Tile at `p`, including the floor and the sand resting on it outside of the grid in part 2.
+fn tile_at<const PART: usize>(c: &Cave, p: Vec2<i64>) -> u8 {
+    match c.grid.get(p) {
+        Some(&tile) => tile,
+        None if PART == 2 && p.y == c.grid.max().y + 2 => ROCK,
+        None if c.extra_floor.contains(&p) => SAND,
+        None => AIR,
+    }
+}
+
+/// Bounds of the grid, and in part 2 of the floor and of the sand resting on it.
+fn cave_bounds<const PART: usize>(c: &Cave) -> Bounds<Vec2<i64>> {
+    let bounds = Bounds { min: c.grid.origin(), max: c.grid.max() };
+    if PART == 1 {
+        return bounds;
     }
+    c.extra_floor.iter().fold(bounds.extend(vec2(bounds.max.x, bounds.max.y + 2)), |b, &p| b.extend(p))
+}
+
+/// Shows the cave around the last unit of sand that came to rest at `sand_pos`.
+fn draw_cave<const PART: usize>(c: &Cave, sand_pos: Vec2<i64>, sand_units: usize) {
+    let view = visualize::view(sand_pos, Some(cave_bounds::<PART>(c).extend(sand_pos)));
+    let body = visualize::render(view, |p| tile_at::<PART>(c, p) as char);
+    visualize::frame(&format!("Day 14 part {PART}: {sand_units} units of sand"), &body);
 }
 
-fn pos_in_grid(g: &Grid, p: Pos) -> bool {
-    (g.min.x..=g.max.x).contains(&p.x) && (g.min.y..=g.max.y).contains(&p.y)
+/// Colours of the air, rock and sand in the exported images.
+const PALETTE: [Rgb; 3] = [Rgb(24, 20, 32), Rgb(112, 108, 120), Rgb(232, 192, 96)];
+
+fn save_cave<const PART: usize>(c: &Cave) {
+    let cave = Image::render(cave_bounds::<PART>(c), |p| match tile_at::<PART>(c, p) {
+        ROCK => 1,
+        SAND => 2,
+        _ => 0,
+    });
+    image::save(&format!("day14-part{PART}"), &cave.scale(3), &PALETTE);
 }
 
-fn sand_can_move_to<const PART: usize>(g: &Grid, new_pos: Pos) -> bool {
-    if pos_in_grid(g, new_pos) {
-        g.grid[new_pos.y - g.min.y][new_pos.x - g.min.x] == AIR
+fn sand_can_move_to<const PART: usize>(c: &Cave, new_pos: Vec2<i64>) -> bool {
+    if let Some(&tile) = c.grid.get(new_pos) {
+        tile == AIR
     } else {
         if PART == 2 {
-            if new_pos.y == g.max.y + 2 { // floor
+            if new_pos.y == c.grid.max().y + 2 { // floor
                 false
             } else {
                 // check sand placed on the floor outside the grid
-                !g.extra_floor.contains(&new_pos)
+                !c.extra_floor.contains(&new_pos)
             }
         } else {
             // part 1, flows out of bounds
@@ -85,14 +103,14 @@ fn sand_can_move_to<const PART: usize>(g: &Grid, new_pos: Pos) -> bool {
     }
 }
 
-fn sand_flow<const PART: usize>(g: &Grid, p: Pos) -> Option<Pos> {
+fn sand_flow<const PART: usize>(c: &Cave, p: Vec2<i64>) -> Option<Vec2<i64>> {
     for new_pos in [
-        pos(p.x, p.y + 1),        // down one step
-        pos(p.x - 1, p.y + 1), // one step down and to the left
-        pos(p.x + 1, p.y + 1), // one step down and to the right
+        p + vec2(0, 1),  // down one step
+        p + vec2(-1, 1), // one step down and to the left
+        p + vec2(1, 1),  // one step down and to the right
     ] {
-        if sand_can_move_to::<PART>(g, new_pos) {
-            return if pos_in_grid(g, new_pos) || PART == 2 {
+        if sand_can_move_to::<PART>(c, new_pos) {
+            return if c.grid.contains(new_pos) || PART == 2 {
                 Some(new_pos)
             } else {
                 None
@@ -103,26 +121,31 @@ fn sand_flow<const PART: usize>(g: &Grid, p: Pos) -> Option<Pos> {
     Some(p)
 }
 
-fn sand_generation<const PART: usize>(g: &mut Grid) -> usize {
+fn sand_generation<const PART: usize>(c: &mut Cave) -> usize {
     let mut sand_units = 0usize;
     'sand_generation: loop {
         let mut sand_pos = SAND_SOURCE;
         'sand_flow: loop {
-            match sand_flow::<PART>(g, sand_pos) {
+            match sand_flow::<PART>(c, sand_pos) {
                 None => break 'sand_generation, // part 1 finished when sand is out of bounds
                 Some(new_sand_pos) => {
                     if new_sand_pos == sand_pos { // comes to rest
                         sand_units += 1;
 
                         // place sand in grid
-                        if pos_in_grid(g, sand_pos) {
-                            g.grid[sand_pos.y - g.min.y][sand_pos.x - g.min.x] = SAND;
+                        if let Some(tile) = c.grid.get_mut(sand_pos) {
+                            *tile = SAND;
                         } else if PART == 2 {
-                            g.extra_floor.insert(sand_pos);
+                            c.extra_floor.insert(sand_pos);
                         }
 
-                        // part 2 finishes when the sand source is reached
-                        if PART == 2 && sand_pos == SAND_SOURCE {
+                        if visualize::enabled() {
+                            draw_cave::<PART>(c, sand_pos, sand_units);
+                        }
+
+                        // part 2 finishes when the sand source is reached, and so does part 1 if
+                        // the rocks hold all the sand
+                        if sand_pos == SAND_SOURCE {
                             break 'sand_generation;
                         }
 
@@ -134,30 +157,71 @@ fn sand_generation<const PART: usize>(g: &mut Grid) -> usize {
             }
         }
     }
+    if image::enabled() {
+        save_cave::<PART>(c);
+    }
     sand_units
 }
 
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input14");
+pub fn parse(input: &str) -> Result<Cave, ParseError> {
     let lines = input
         .lines()
-        .map(|l| l.split(" -> ")
-            .map(|pos_str| pos_str.split_once(',').unwrap())
-            .map(|(x_str, y_str)| pos(x_str.parse().unwrap(), y_str.parse().unwrap()))
-            .collect::<Vec<_>>()
-        )
-        .collect::<Vec<_>>();
-
-    let mut grid = build_grid(&lines);
-    let mut grid2 = grid.clone();
-    // print_grid(&grid);
-
-    let res1 = sand_generation::<1>(&mut grid);
-    let res2 = sand_generation::<2>(&mut grid2);
-    // print_grid(&grid);
-    // print_grid(&grid2);
-    println!("[Part 1] Result is {res1}");
-    println!("[Part 2] Result is {res2}");
+        .map(|l| {
+            let mut prev: Option<Vec2<i64>> = None;
+            l.split(" -> ")
+                .map(|pos_str| {
+                    let (x_str, y_str) = parser::split_once(input, pos_str, ",")?;
+                    let p = vec2(parser::number(input, x_str)?, parser::number(input, y_str)?);
+                    if prev.is_some_and(|prev| prev.x != p.x && prev.y != p.y) {
+                        return Err(ParseError::at(input, pos_str, "a point in a horizontal or vertical line with the previous one"));
+                    }
+                    prev = Some(p);
+                    Ok(p)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(build_cave(&lines))
+}
+
+pub fn part1(cave: &Cave) -> usize {
+    sand_generation::<1>(&mut cave.clone())
+}
+
+pub fn part2(cave: &Cave) -> usize {
+    sand_generation::<2>(&mut cave.clone())
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = Cave;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example14");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 24);
+        assert_eq!(part2(&input), 93);
+    }
 }
@@ -1,46 +1,66 @@
 use std::collections::HashSet;
 
-mod profiler;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Pos { x: i64, y: i64 }
-const fn pos(x: i64, y: i64) -> Pos { Pos { x, y } }
-
-const fn manhattan(a: Pos, b: Pos) -> i64 {
-    (a.x - b.x).abs() + (a.y - b.y).abs()
-}
+use crate::combinator::{self, map, number, preceded, tag};
+use crate::geom::{vec2, Vec2};
+use crate::image::{self, Image, Rgb};
+use crate::parser::ParseError;
+use crate::{parallel, Answer, Solution};
 
 #[derive(Debug, Copy, Clone)]
-struct Sensor {
-    pos: Pos,
-    closest_beacon: Pos,
+pub struct Sensor {
+    pos: Vec2<i64>,
+    closest_beacon: Vec2<i64>,
     dist_to_closest_beacon: i64,
 }
 
-fn main() {
-    let _p = profiler::profile();
+/// The sensors, with the row checked in part 1 and the largest coordinate of the distress beacon in
+/// part 2, which are smaller in the example.
+#[derive(Debug, Clone)]
+pub struct Report {
+    sensors: Vec<Sensor>,
+    y: i64,
+    size: i64,
+}
 
-    let input = include_str!("data/input15");
-    let sensors = input
+fn parse_sensors(input: &str) -> Result<Vec<Sensor>, ParseError> {
+    input
         .lines()
         .map(|l| {
-            fn parse_pos(s: &str) -> Option<Pos> {
-                let (xs, ys) = s.split_once(", ")?;
-                Some(pos(
-                    xs.split_once("=")?.1.parse().ok()?,
-                    ys.split_once("=")?.1.parse().ok()?,
-                ))
-            }
-
-            let sensor_pos = parse_pos(l.trim_start_matches("Sensor at ").split_once(":")?.0)?;
-            let closest_beacon = parse_pos(l.rsplit_once(" at ")?.1)?;
-            Some(Sensor {
+            let pos = || map(
+                (preceded(tag("x="), number()), preceded(tag(", y="), number())),
+                |(x, y)| vec2(x, y),
+            );
+            let (sensor_pos, closest_beacon) = combinator::parse_line(input, l, (
+                preceded(tag("Sensor at "), pos()),
+                preceded(tag(": closest beacon is at "), pos()),
+            ))?;
+            Ok(Sensor {
                 pos: sensor_pos,
                 closest_beacon,
-                dist_to_closest_beacon: manhattan(sensor_pos, closest_beacon),
+                dist_to_closest_beacon: sensor_pos.manhattan(closest_beacon),
             })
-        }).collect::<Option<Vec<_>>>().unwrap();
+        }).collect()
+}
+
+pub fn parse(input: &str) -> Result<Report, ParseError> {
+    Ok(Report { sensors: parse_sensors(input)?, y: Y, size: SIZE })
+}
+
+/// Like [`parse`], but with the row and search area of the example.
+pub fn parse_example(input: &str) -> Result<Report, ParseError> {
+    Ok(Report { sensors: parse_sensors(input)?, y: EXAMPLE_Y, size: EXAMPLE_SIZE })
+}
+
+/// Row checked in part 1.
+const Y: i64 = 2000000;
+/// Maximum coordinate of the distress beacon in part 2.
+const SIZE: i64 = 4000000;
+/// [`Y`] and [`SIZE`] in the example, which is much smaller.
+const EXAMPLE_Y: i64 = 10;
+const EXAMPLE_SIZE: i64 = 20;
 
+/// Number of positions in row `y` where a beacon cannot be.
+fn count_positions_without_beacon(sensors: &[Sensor], y: i64) -> usize {
     let (min_x, max_x) = sensors.iter()
         .flat_map(|l| [l.pos.x - l.dist_to_closest_beacon + 1, l.pos.x + l.dist_to_closest_beacon - 1])
         .fold((i64::MAX, i64::MIN), |acc, n| (acc.0.min(n), acc.1.max(n)));
@@ -48,42 +68,115 @@ fn main() {
     // println!("{:#?}", sensors);
     // println!("{:?}", (min_x, max_x));
 
-    const Y: i64 = 2000000;
     let num_beacons_at_y = sensors.iter()
-        .filter_map(|s| if s.closest_beacon.y == Y { Some(s.closest_beacon.x) } else { None })
+        .filter_map(|s| if s.closest_beacon.y == y { Some(s.closest_beacon.x) } else { None })
         .fold(HashSet::new(), |mut acc, n| {
             acc.insert(n); acc
         }).len();
 
-    let res1 = (min_x..=max_x)
+    (min_x..=max_x)
         .filter(|&x| {
-            let p = pos(x, Y);
-            sensors.iter().any(|s| manhattan(s.pos, p) <= s.dist_to_closest_beacon)
+            let p = vec2(x, y);
+            sensors.iter().any(|s| s.pos.manhattan(p) <= s.dist_to_closest_beacon)
         })
-        .count() - num_beacons_at_y;
+        .count() - num_beacons_at_y
+}
 
-    const SIZE: i64 = 4000000;
-    let sensors2 = sensors.clone();
-    let mut distress_beacon = None;
-    'outer: for s in &sensors {
+/// Tuning frequency of the only position, with coordinates between 0 and `size`, that no sensor covers.
+fn tuning_frequency(sensors: &[Sensor], size: i64) -> i64 {
+    // the distress beacon is next to the area of a sensor, the perimeters are walked in parallel
+    let distress_beacon = parallel::find_map(sensors, |s| {
         let r = s.dist_to_closest_beacon + 1;
-        for x in -r..=r {
+        (-r..=r).find_map(|x| {
             let h = (r - x).abs();
-            for p in [
-                pos(s.pos.x + x, s.pos.y - h),
-                pos(s.pos.x + x, s.pos.y + h),
-            ] {
-                if (0..=SIZE).contains(&p.x) && (0..=SIZE).contains(&p.y) &&
-                    sensors2.iter().all(|s| manhattan(s.pos, p) > s.dist_to_closest_beacon) {
-                    distress_beacon = Some(p);
-                    break 'outer;
-                }
-            }
+            [s.pos + vec2(x, -h), s.pos + vec2(x, h)].into_iter().find(|&p| {
+                (0..=size).contains(&p.x) && (0..=size).contains(&p.y) &&
+                    sensors.iter().all(|s| s.pos.manhattan(p) > s.dist_to_closest_beacon)
+            })
+        })
+    });
+    let distress_beacon = distress_beacon.unwrap();
+    if image::enabled() {
+        save_sensors(sensors, size, distress_beacon);
+    }
+    distress_beacon.x * 4000000 + distress_beacon.y
+}
+
+/// Colours of the exported image: uncovered positions, sensors, the distress beacon, then the areas
+/// covered by the sensors.
+const PALETTE: [Rgb; 9] = [
+    Rgb(16, 16, 24),
+    Rgb(255, 255, 255),
+    Rgb(255, 48, 48),
+    Rgb(64, 96, 160),
+    Rgb(64, 140, 120),
+    Rgb(120, 88, 160),
+    Rgb(160, 120, 64),
+    Rgb(96, 144, 64),
+    Rgb(150, 72, 104),
+];
+/// Width and height of the exported image.
+const IMAGE_SIZE: usize = 600;
+
+/// Saves the diamonds covered by the sensors within 0..=`size`, scaled down to [`IMAGE_SIZE`].
+fn save_sensors(sensors: &[Sensor], size: i64, distress_beacon: Vec2<i64>) {
+    let to_pixel = |n: i64| (n * (IMAGE_SIZE as i64 - 1) / size) as usize;
+    let markers = sensors.iter().map(|s| (s.pos, 2, 1)).chain([(distress_beacon, 4, 2)]).collect::<Vec<_>>();
+    let image = Image::new(IMAGE_SIZE, IMAGE_SIZE, |x, y| {
+        let marker = markers.iter().find(|(p, r, _)| to_pixel(p.x).abs_diff(x) + to_pixel(p.y).abs_diff(y) <= *r);
+        if let Some(&(_, _, colour)) = marker {
+            return colour;
+        }
+        let p = vec2(x as i64, y as i64) * size / (IMAGE_SIZE as i64 - 1);
+        match sensors.iter().position(|s| s.pos.manhattan(p) <= s.dist_to_closest_beacon) {
+            Some(i) => 3 + (i % (PALETTE.len() - 3)) as u8,
+            None => 0,
         }
+    });
+    image::save("day15", &image, &PALETTE);
+}
+
+pub fn part1(report: &Report) -> usize {
+    count_positions_without_beacon(&report.sensors, report.y)
+}
+
+pub fn part2(report: &Report) -> i64 {
+    tuning_frequency(&report.sensors, report.size)
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = Report;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-    let distress_beacon = distress_beacon.unwrap();
-    let res2 = distress_beacon.x * 4000000 + distress_beacon.y;
 
-    println!("[Part 1] Result is {res1}");
-    println!("[Part 2] Result is {res2}");
+    fn parse_example(input: &str) -> Result<Self::Input, ParseError> {
+        parse_example(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example15");
+
+    #[test]
+    fn test_example() {
+        let input = parse_example(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 26);
+        assert_eq!(part2(&input), 56000011);
+    }
 }
@@ -1,33 +1,30 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
-mod profiler;
+use crate::combinator::{self, alt, delimited, ident, map, number, preceded, sep_by1, tag, verify};
+use crate::parser::ParseError;
+use crate::{parallel, search};
+use crate::{Answer, Solution};
 
 #[derive(Debug, Clone)]
-struct Valve {
+pub struct Valve {
     id: u16,
     rate: u64,
     connections: Vec<u16>,
 }
 
 impl Valve {
-
-    fn parse(s: &str) -> Option<Valve> {
-        let id = Self::parse_id(s.split_once("Valve ")?.1.as_bytes());
-        let rate_str = s.split_once("rate=")?.1.split_once(';')?.0;
-        let rate = rate_str.parse::<u64>().ok()?;
-        let connections = s.rsplit(", ")
-            .map(|c| {
-                let c = c.as_bytes();
-                let id_str = [c[c.len() - 2], c[c.len() - 1]];
-                Self::parse_id(&id_str)
-            })
-            .collect::<Vec<u16>>();
-
-        Some(Valve {
-            id,
-            rate,
-            connections,
-        })
+    fn parse(input: &str, s: &str) -> Result<Valve, ParseError> {
+        // Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+        let name = || map(
+            verify(ident(), |n: &&str| n.len() == 2 && n.bytes().all(|c| c.is_ascii_uppercase()), "a two-letter valve name"),
+            |n| Self::parse_id(n.as_bytes()),
+        );
+        let (id, rate, connections) = combinator::parse_line(input, s, (
+            preceded(tag("Valve "), name()),
+            delimited(tag(" has flow rate="), number(), tag("; ")),
+            preceded(alt(tag("tunnels lead to valves "), tag("tunnel leads to valve ")), sep_by1(name(), tag(", "))),
+        ))?;
+        Ok(Valve { id, rate, connections })
     }
 
     fn parse_id(id_str: &[u8]) -> u16 {
@@ -35,37 +32,27 @@ impl Valve {
     }
 }
 
+/// Valves keyed by their two-letter name packed into a `u16`.
+pub type Valves = HashMap<u16, Valve>;
+
+/// The valve we start at, `AA`.
+const START: u16 = (b'A' as u16) << 8 | b'A' as u16;
+
 #[allow(dead_code)]
 fn id_str(id: u16) -> String {
     let c = [((id >> 8) & 0xFF) as u8, (id & 0xFF) as u8];
     std::str::from_utf8(&c).unwrap().into()
 }
 
-fn pathfind_bfs(map: &HashMap<u16, Valve>, start: u16, end: u16) -> Option<Vec<u16>> {
-    type Step = (u16, Vec<u16>);
-
-    let mut visited: HashSet<u16> = HashSet::new();
-    let mut queue = VecDeque::<Step>::new();
-    visited.insert(start);
-    queue.push_back((start, Vec::new()));
-    while let Some((curr, path)) = queue.pop_front() {
-        if curr == end {
-            return Some(path);
-        }
-        for conn in &map.get(&curr)?.connections  {
-            if !visited.contains(&conn) {
-                let mut new_path = path.clone();
-                new_path.push(*conn);
-                queue.push_back((*conn, new_path));
-            }
-        };
-    }
-
-    return None;
+/// Number of steps from `start` to `end` through the tunnels.
+fn distance(map: &Valves, start: u16, end: u16) -> Option<u64> {
+    let successors = |v: &u16| map.get(v).into_iter().flat_map(|v| v.connections.iter().copied());
+    search::bfs_cost(start, successors, |&v| v == end).cost.map(|cost| cost as u64)
 }
 
-type ValvePaths = HashMap<(u16, u16), Option<Vec<u16>>>;
-fn build_paths(map: &HashMap<u16, Valve>) -> ValvePaths {
+/// Distances between every pair of different valves, `None` if one can't be reached from the other.
+type ValvePaths = HashMap<(u16, u16), Option<u64>>;
+fn build_paths(map: &Valves) -> ValvePaths {
     let ids = map.keys().collect::<Vec<_>>();
     let mut paths = ValvePaths::new();
     for from_idx in 0..ids.len() {
@@ -73,16 +60,15 @@ fn build_paths(map: &HashMap<u16, Valve>) -> ValvePaths {
             if from_idx != to_idx {
                 let from = *ids[from_idx];
                 let to = *ids[to_idx];
-                let path = pathfind_bfs(&map, from, to);
-                paths.insert((from, to), path);
+                paths.insert((from, to), distance(map, from, to));
             }
         }
     }
     paths
 }
 
-fn explore(map: &HashMap<u16, Valve>) -> u64 {
-    fn explore_rec(map: &HashMap<u16, Valve>, paths: &ValvePaths, from: u16, open_valves: &mut HashSet<u16>, time_remaining: u64) -> u64 {
+fn explore(map: &Valves) -> u64 {
+    fn explore_rec(map: &Valves, paths: &ValvePaths, from: u16, open_valves: &mut HashSet<u16>, time_remaining: u64) -> u64 {
         let valve = map.get(&from).unwrap();
 
         let mut total_pressure_released = 0u64;
@@ -102,11 +88,11 @@ fn explore(map: &HashMap<u16, Valve>) -> u64 {
                 continue;
             }
 
-            let Some(path) = paths.get(&(from, target_valve_id)).unwrap() else {
+            let Some(distance) = *paths.get(&(from, target_valve_id)).unwrap() else {
                 continue;
             };
 
-            let path_and_open_time = path.len() as u64 + 1;
+            let path_and_open_time = distance + 1;
             if time_remaining < path_and_open_time {
                 continue;
             }
@@ -123,18 +109,30 @@ fn explore(map: &HashMap<u16, Valve>) -> u64 {
             }
         }
 
-        if let Some(_) = best_target_valve_id {
+        if best_target_valve_id.is_some() {
             total_pressure_released += best_pressure_released;
         }
 
         total_pressure_released
     }
 
-    explore_rec(map, &build_paths(map), Valve::parse_id("AA".as_bytes()), &mut HashSet::new(), 30)
+    explore_rec(map, &build_paths(map), START, &mut HashSet::new(), 30)
 }
 
-fn explore2(map: &HashMap<u16, Valve>) -> u64 {
-    fn explore_rec(map: &HashMap<u16, Valve>, paths: &ValvePaths, from1: u16, from2: u16, open_valves: &mut HashSet<u16>, time_remaining1: u64, time_remaining2: u64) -> u64 {
+/// Closed valves with a flow rate that can be reached from `from` and opened in `time_remaining`, with
+/// the time that is left once they are.
+fn moves<'a>(map: &'a Valves, paths: &'a ValvePaths, from: u16, open_valves: &'a HashSet<u16>, time_remaining: u64) -> impl Iterator<Item = (u16, u64)> + 'a {
+    map.iter().filter_map(move |(&target_valve_id, target_valve)| {
+        if from == target_valve_id || open_valves.contains(&target_valve_id) || target_valve.rate == 0 {
+            return None;
+        }
+        let path_and_open_time = (*paths.get(&(from, target_valve_id)).unwrap())? + 1;
+        time_remaining.checked_sub(path_and_open_time).map(|time_remaining| (target_valve_id, time_remaining))
+    })
+}
+
+fn explore2(map: &Valves) -> u64 {
+    fn explore_rec(map: &Valves, paths: &ValvePaths, from1: u16, from2: u16, open_valves: &mut HashSet<u16>, time_remaining1: u64, time_remaining2: u64) -> u64 {
         let valve1 = map.get(&from1).unwrap();
         let valve2 = map.get(&from2).unwrap();
 
@@ -153,145 +151,121 @@ fn explore2(map: &HashMap<u16, Valve>) -> u64 {
         }
 
         let mut best_pressure_released = 0u64;
-        let mut best_target_valve_id = None;
         if time_remaining1 > 0 && time_remaining2 > 0 { // both me and elephant move
-            for &target_valve_id1 in map.keys() {
-                if from1 == target_valve_id1 || open_valves.contains(&target_valve_id1) {
-                    continue;
-                }
-
-                let Some(path1) = paths.get(&(from1, target_valve_id1)).unwrap() else {
-                    continue;
-                };
-
-                let path_and_open_time1 = path1.len() as u64 + 1;
-                if time_remaining1 < path_and_open_time1 {
-                    continue;
-                }
-
-                let target_valve1 = map.get(&target_valve_id1).unwrap();
-                if target_valve1.rate == 0 {
-                    continue;
-                }
-
-                for &target_valve_id2 in map.keys() {
-                    if from2 == target_valve_id2 || target_valve_id1 == target_valve_id2 || open_valves.contains(&target_valve_id2) {
-                        continue;
-                    }
-
-                    let Some(path2) = paths.get(&(from2, target_valve_id2)).unwrap() else {
-                        continue;
-                    };
-
-                    let path_and_open_time2 = path2.len() as u64 + 1;
-                    if time_remaining2 < path_and_open_time2 {
-                        continue;
-                    }
-
-                    let target_valve2 = map.get(&target_valve_id2).unwrap();
-                    if target_valve2.rate == 0 {
-                        continue;
-                    }
-
-                    let pressure_released = explore_rec(map, paths,
-                                                        target_valve_id1, target_valve_id2,
-                                                        &mut open_valves.clone(),
-                                                        time_remaining1 - path_and_open_time1,
-                                                        time_remaining2 - path_and_open_time2);
-                    if pressure_released > best_pressure_released {
-                        best_pressure_released = pressure_released;
-                        best_target_valve_id = Some((target_valve_id1, target_valve_id2));
+            for (target_valve_id1, time_remaining1) in moves(map, paths, from1, open_valves, time_remaining1) {
+                for (target_valve_id2, time_remaining2) in moves(map, paths, from2, open_valves, time_remaining2) {
+                    if target_valve_id1 != target_valve_id2 {
+                        let pressure_released = explore_rec(map, paths,
+                                                            target_valve_id1, target_valve_id2,
+                                                            &mut open_valves.clone(),
+                                                            time_remaining1, time_remaining2);
+                        best_pressure_released = best_pressure_released.max(pressure_released);
                     }
                 }
             }
         } else if time_remaining1 > 0 { // only I move
-            for &target_valve_id in map.keys() {
-                if from1 == target_valve_id || open_valves.contains(&target_valve_id) {
-                    continue;
-                }
-
-                let Some(path) = paths.get(&(from1, target_valve_id)).unwrap() else {
-                    continue;
-                };
-
-                let path_and_open_time = path.len() as u64 + 1;
-                if time_remaining1 < path_and_open_time {
-                    continue;
-                }
-
-                let target_valve = map.get(&target_valve_id).unwrap();
-                if target_valve.rate == 0 {
-                    continue;
-                }
-
+            for (target_valve_id, time_remaining1) in moves(map, paths, from1, open_valves, time_remaining1) {
                 let pressure_released = explore_rec(map, paths,
                                                     target_valve_id, from2, &mut open_valves.clone(),
-                                                    time_remaining1 - path_and_open_time,
-                                                    0);
-                if pressure_released > best_pressure_released {
-                    best_pressure_released = pressure_released;
-                    best_target_valve_id = Some((target_valve_id, 0xFFFF));
-                }
+                                                    time_remaining1, 0);
+                best_pressure_released = best_pressure_released.max(pressure_released);
             }
         } else if time_remaining2 > 0 { // only elephant moves
-            for &target_valve_id in map.keys() {
-                if from2 == target_valve_id || open_valves.contains(&target_valve_id) {
-                    continue;
-                }
-
-                let Some(path) = paths.get(&(from2, target_valve_id)).unwrap() else {
-                    continue;
-                };
-
-                let path_and_open_time = path.len() as u64 + 1;
-                if time_remaining2 < path_and_open_time {
-                    continue;
-                }
-
-                let target_valve = map.get(&target_valve_id).unwrap();
-                if target_valve.rate == 0 {
-                    continue;
-                }
-
+            for (target_valve_id, time_remaining2) in moves(map, paths, from2, open_valves, time_remaining2) {
                 let pressure_released = explore_rec(map, paths,
                                                     from1, target_valve_id, &mut open_valves.clone(),
-                                                    0,
-                                                    time_remaining2 - path_and_open_time);
-                if pressure_released > best_pressure_released {
-                    best_pressure_released = pressure_released;
-                    best_target_valve_id = Some((0xFFFF, target_valve_id));
-                }
+                                                    0, time_remaining2);
+                best_pressure_released = best_pressure_released.max(pressure_released);
             }
         }
 
-        if let Some(_) = best_target_valve_id {
-            total_pressure_released += best_pressure_released;
-        }
-
-        total_pressure_released
+        total_pressure_released + best_pressure_released
     }
 
-    let start = Valve::parse_id("AA".as_bytes());
-    explore_rec(map, &build_paths(map), start, start, &mut HashSet::new(), 26, 26)
+    // the first moves of both me and the elephant are explored independently, in parallel, with AA
+    // opened first if it has a flow rate like explore_rec does
+    let paths = build_paths(map);
+    let mut open_valves = HashSet::new();
+    let start_rate = map[&START].rate;
+    if start_rate > 0 {
+        open_valves.insert(START);
+    }
+    let first_moves = moves(map, &paths, START, &open_valves, 26)
+        .flat_map(|first1| moves(map, &paths, START, &open_valves, 26).map(move |first2| (first1, first2)))
+        .filter(|((target_valve_id1, _), (target_valve_id2, _))| target_valve_id1 != target_valve_id2)
+        .collect::<Vec<_>>();
+    let best_pressure_released = parallel::map(&first_moves, |&((target_valve_id1, time_remaining1), (target_valve_id2, time_remaining2))| {
+        explore_rec(map, &paths, target_valve_id1, target_valve_id2, &mut open_valves.clone(), time_remaining1, time_remaining2)
+    });
+    26 * start_rate + best_pressure_released.into_iter().max().unwrap_or(0)
 }
 
-
-
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input16");
+pub fn parse(input: &str) -> Result<Valves, ParseError> {
     let valves = input
         .lines()
-        .map(Valve::parse)
-        .collect::<Option<Vec<_>>>().unwrap();
-    let valves_map = valves.iter().fold(HashMap::new(), |mut map, v| {
+        .map(|l| Valve::parse(input, l))
+        .collect::<Result<Vec<_>, _>>()?;
+    let map = valves.iter().fold(HashMap::new(), |mut map, v| {
         map.insert(v.id, v.clone());
         map
     });
 
-    let res1 = explore(&valves_map);
-    println!("[Part 1] Result is {res1}");
-    let res2 = explore2(&valves_map);
-    println!("[Part 2] Result is {res2}");
+    if !map.contains_key(&START) {
+        return Err(ParseError::after(input, input, "a valve AA"));
+    }
+    for (l, v) in input.lines().zip(&valves) {
+        if !v.connections.iter().all(|c| map.contains_key(c)) {
+            return Err(ParseError::at(input, l, "tunnels to valves defined in the file"));
+        }
+    }
+    Ok(map)
+}
+
+pub fn part1(valves: &Valves) -> u64 {
+    explore(valves)
+}
+
+pub fn part2(valves: &Valves) -> u64 {
+    explore2(valves)
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Input = Valves;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example16");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 1651);
+        assert_eq!(part2(&input), 1707);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("Valve BB has flow rate=0; tunnel leads to valve CC\nValve CC has flow rate=1; tunnel leads to valve BB\n").unwrap_err();
+        assert_eq!(err.expected, "a valve AA");
+        let err = parse("Valve AA has flow rate=0; tunnels lead to valves BB, CC\nValve BB has flow rate=1; tunnel leads to valve AA\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
 }
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
-
-mod profiler;
+use crate::cycle;
+use crate::parser::ParseError;
+use crate::visualize;
+use crate::{Answer, Solution};
 
 #[derive(Debug, Copy, Clone)]
 struct Rock {
@@ -76,7 +77,7 @@ fn rock_will_collide_right_wall(r: Rock) -> bool {
     r.shape.iter().map(|n| n.trailing_zeros()).min().unwrap() == 0
 }
 
-fn rock_overlaps_rocks(r: Rock, stack: &Vec<u8>, y: usize) -> bool {
+fn rock_overlaps_rocks(r: Rock, stack: &[u8], y: usize) -> bool {
     for h in 0..r.shape.len() {
         let rock_row = r.shape[r.shape.len() - 1 - h];
         if rock_row == 0 {
@@ -92,18 +93,18 @@ fn rock_overlaps_rocks(r: Rock, stack: &Vec<u8>, y: usize) -> bool {
         }
     }
 
-    return false;
+    false
 }
 
-fn rock_can_move_left(r: Rock, stack: &Vec<u8>, y: usize) -> bool {
+fn rock_can_move_left(r: Rock, stack: &[u8], y: usize) -> bool {
     !rock_will_collide_left_wall(r) && !rock_overlaps_rocks(r.push_left(), stack, y)
 }
 
-fn rock_can_move_right(r: Rock, stack: &Vec<u8>, y: usize) -> bool {
+fn rock_can_move_right(r: Rock, stack: &[u8], y: usize) -> bool {
     !rock_will_collide_right_wall(r) && !rock_overlaps_rocks(r.push_right(), stack, y)
 }
 
-fn rock_can_move_down(r: Rock, stack: &Vec<u8>, y: usize) -> bool {
+fn rock_can_move_down(r: Rock, stack: &[u8], y: usize) -> bool {
     if y == 0 {
         return false;
     }
@@ -126,142 +127,131 @@ fn rock_place(r: Rock, stack: &mut Vec<u8>, y: usize) {
     }
 }
 
-const INPUT: &str = include_str!("data/input17");
-
-fn generate_rocks(num_rocks_to_generate: usize) -> Vec<u8> {
-    let pattern = INPUT.trim().as_bytes();
-    let mut stack = Vec::new();
-    let mut rock_idx = 0usize;
-    let mut pattern_idx = 0usize;
-    let mut num_rocks = 0usize;
-    'rock_generator: loop {
-        num_rocks += 1;
-
-        let mut rock = ROCKS[rock_idx];
-        rock_idx = (rock_idx + 1) % ROCKS.len();
-
-        'rock_falling: for y in (0..=(stack.len() + 3)).rev() {
-            // println!(" -- y:{y} -- ");
-            // println!("====================");
-            // let mut stack_c = stack.clone();
-            // for _ in 0..5 { stack_c.push(0); }
-            // rock_place(rock, &mut stack_c, y);
-            // for row in stack_c.iter().rev() {
-            //     println!("{:07b}", *row);
-            // }
-            // println!("====================");
-
-            match pattern[pattern_idx] {
+/// Number of rows at the top of the stack taken as the key of the state for the cycle detection. This is
+/// a heuristic window: a rock falling down a shaft deeper than 32 rows would reach rows left out of the
+/// key, and two states could then match without being a cycle. It is deep enough for the example and
+/// the puzzle input.
+const TOP_ROWS: usize = 32;
+
+struct Chamber<'a> {
+    pattern: &'a [u8],
+    stack: Vec<u8>,
+    rock_idx: usize,
+    pattern_idx: usize,
+}
+
+impl<'a> Chamber<'a> {
+    fn new(pattern: &'a [u8]) -> Self {
+        Chamber { pattern, stack: Vec::new(), rock_idx: 0, pattern_idx: 0 }
+    }
+
+    fn drop_rock(&mut self) {
+        let stack = &mut self.stack;
+        let mut rock = ROCKS[self.rock_idx];
+        self.rock_idx = (self.rock_idx + 1) % ROCKS.len();
+
+        for y in (0..=(stack.len() + 3)).rev() {
+            match self.pattern[self.pattern_idx] {
                 b'<' => {
-                    if rock_can_move_left(rock, &stack, y) {
+                    if rock_can_move_left(rock, stack, y) {
                         rock = rock.push_left();
                     }
                 },
                 b'>' => {
-                    if rock_can_move_right(rock, &stack, y) {
+                    if rock_can_move_right(rock, stack, y) {
                         rock = rock.push_right();
                     }
                 },
                 c => panic!("unexpected character in pattern '{:?}'", std::char::from_u32(c as u32))
             }
-            pattern_idx = (pattern_idx + 1) % pattern.len();
+            self.pattern_idx = (self.pattern_idx + 1) % self.pattern.len();
 
-            if !rock_can_move_down(rock, &stack, y) {
-                rock_place(rock, &mut stack, y);
-                break 'rock_falling;
+            if !rock_can_move_down(rock, stack, y) {
+                rock_place(rock, stack, y);
+                break;
             }
         }
+    }
 
-        if num_rocks == num_rocks_to_generate {
-            break 'rock_generator;
+    fn key(&self) -> (usize, usize, [u8; TOP_ROWS]) {
+        let mut top = [0; TOP_ROWS];
+        for (t, row) in top.iter_mut().zip(self.stack.iter().rev()) {
+            *t = *row;
         }
+        (self.rock_idx, self.pattern_idx, top)
     }
-
-    stack
 }
 
-fn generate_rocks2(num_rocks_to_generate: usize) -> usize {
-    let pattern = INPUT.trim().as_bytes();
-    let mut stack = Vec::new();
-    let mut rock_idx = 0usize;
-    let mut pattern_idx = 0usize;
-    let mut num_rocks = 0usize;
-
-    struct CycleState { num_rocks: usize, height: usize }
-    let mut cycle_cache = HashMap::new();
-
-    'rock_generator: loop {
-        num_rocks += 1;
-
-        let start_rock_idx = rock_idx;
-        let start_pattern_idx = pattern_idx;
+fn generate_rocks(pattern: &[u8], num_rocks_to_generate: usize) -> Vec<u8> {
+    let mut chamber = Chamber::new(pattern);
+    for num_rocks in 1..=num_rocks_to_generate {
+        chamber.drop_rock();
+        if visualize::enabled() {
+            draw_tower(&chamber.stack, num_rocks);
+        }
+    }
+    chamber.stack
+}
 
-        let mut rock = ROCKS[rock_idx];
-        rock_idx = (rock_idx + 1) % ROCKS.len();
+/// Shows the top of the tower, as much as fits in a view.
+fn draw_tower(stack: &[u8], num_rocks: usize) {
+    let mut body = String::new();
+    for row in stack.iter().rev().take(visualize::VIEW_SIZE.y as usize - 1) {
+        body.push('|');
+        body.extend((0..7).rev().map(|bit| if row & (1 << bit) != 0 { '#' } else { '.' }));
+        body.push_str("|\n");
+    }
+    if stack.len() < visualize::VIEW_SIZE.y as usize {
+        body.push_str("+-------+\n");
+    }
+    visualize::frame(&format!("Day 17: {num_rocks} rocks, tower {} units tall", stack.len()), &body);
+}
 
-        'rock_falling: for y in (0..=(stack.len() + 3)).rev() {
-            match pattern[pattern_idx] {
-                b'<' => {
-                    if rock_can_move_left(rock, &stack, y) {
-                        rock = rock.push_left();
-                    }
-                },
-                b'>' => {
-                    if rock_can_move_right(rock, &stack, y) {
-                        rock = rock.push_right();
-                    }
-                },
-                c => panic!("unexpected character in pattern '{:?}'", std::char::from_u32(c as u32))
-            }
-            pattern_idx = (pattern_idx + 1) % pattern.len();
+fn generate_rocks2(pattern: &[u8], num_rocks_to_generate: usize) -> usize {
+    cycle::metric_after(Chamber::new(pattern), Chamber::drop_rock, Chamber::key, |c| c.stack.len(), num_rocks_to_generate)
+}
 
-            if !rock_can_move_down(rock, &stack, y) {
-                rock_place(rock, &mut stack, y);
-                break 'rock_falling;
-            }
-        }
+pub fn parse(input: &str) -> Result<Vec<u8>, ParseError> {
+    let pattern = input.trim();
+    match pattern.char_indices().find(|&(_, c)| c != '<' && c != '>') {
+        Some((i, _)) => Err(ParseError::at(input, &pattern[i..], "'<' or '>'")),
+        None if pattern.is_empty() => Err(ParseError::at(input, pattern, "a jet pattern")),
+        None => Ok(pattern.as_bytes().to_vec()),
+    }
+}
 
-        if let Some(prev) = cycle_cache.insert((start_rock_idx, start_pattern_idx), CycleState {
-            num_rocks,
-            height: stack.len(),
-        }) {
-            let rocks_per_cycle = num_rocks - prev.num_rocks;
-            let remaining_rocks = num_rocks_to_generate - num_rocks;
-            if remaining_rocks % rocks_per_cycle == 0 { // start of a new cycle
-                let remaining_cycles = remaining_rocks / rocks_per_cycle;
-                let cycle_height = stack.len() - prev.height;
-                let remaining_height = remaining_cycles * cycle_height;
-                let total_height = stack.len() + remaining_height;
-                return total_height;
-            }
-        }
+pub fn part1(pattern: &[u8]) -> usize {
+    let stack = generate_rocks(pattern, 2022);
 
-        if num_rocks == num_rocks_to_generate {
-            break 'rock_generator;
-        }
+    crate::trace!("====================");
+    for row in stack.iter().rev() {
+        crate::trace!("{:07b}", *row);
     }
+    crate::trace!("====================");
 
     stack.len()
 }
 
-fn main() {
-    let _p = profiler::profile();
+pub fn part2(pattern: &[u8]) -> usize {
+    generate_rocks2(pattern, 1000000000000)
+}
 
-    {
-        let res1_stack = generate_rocks(2022);
+pub struct Day17;
 
-        println!("====================");
-        for row in res1_stack.iter().rev() {
-            println!("{:07b}", *row);
-        }
-        println!("====================");
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Input = Vec<u8>;
 
-        let res1 = res1_stack.len();
-        println!("[Part 1] Result is {res1}");
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-    {
-        let res2 = generate_rocks2(1000000000000);
-        println!("[Part 2] Result is {res2}");
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
     }
 }
 
@@ -270,6 +260,8 @@ fn main() {
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = include_str!("data/example17");
+
     #[test]
     fn test_collision() {
         let test_stack = vec![
@@ -288,4 +280,11 @@ mod tests {
         };
         assert!(!rock_can_move_right(rock, &test_stack, 3));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 3068);
+        assert_eq!(part2(&input), 1514285714288);
+    }
+}
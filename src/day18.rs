@@ -1,49 +1,28 @@
 use std::collections::{HashSet, VecDeque};
-use std::ops;
 
-mod profiler;
+use crate::geom::{vec3, Bounds, Vec3};
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Pos { x: i64, y: i64, z: i64 }
-const fn pos(x: i64, y: i64, z: i64) -> Pos { Pos { x, y, z } }
-
-impl ops::Add for Pos {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self {
-        pos(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
-    }
-}
-
-impl ops::Sub for Pos {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self {
-        pos(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
-    }
-}
-
-const INPUT: &str = include_str!("data/input18");
-
-fn get_neighbors(p: Pos) -> impl Iterator<Item=Pos> {
+fn get_neighbors(p: Vec3<i64>) -> impl Iterator<Item=Vec3<i64>> {
     [
-        pos(1, 0, 0),
-        pos(-1, 0, 0),
-        pos(0, 1, 0),
-        pos(0, -1, 0),
-        pos(0, 0, 1),
-        pos(0, 0, -1),
+        vec3(1, 0, 0),
+        vec3(-1, 0, 0),
+        vec3(0, 1, 0),
+        vec3(0, -1, 0),
+        vec3(0, 0, 1),
+        vec3(0, 0, -1),
     ].into_iter()
         .map(move |offset| p + offset)
 }
 
-fn count_neighbors(set: &HashSet<Pos>, p: Pos) -> usize {
+fn count_neighbors(set: &HashSet<Vec3<i64>>, p: Vec3<i64>) -> usize {
     get_neighbors(p)
         .filter(|neighbor_pos| set.contains(neighbor_pos))
         .count()
 }
 
-fn measure_surface_area(cubes: &Vec<Pos>) -> usize {
+fn measure_surface_area(cubes: &[Vec3<i64>]) -> usize {
     let mut set = HashSet::new();
     let mut surface_area = 0usize;
     for cube in cubes {
@@ -54,35 +33,15 @@ fn measure_surface_area(cubes: &Vec<Pos>) -> usize {
     surface_area
 }
 
-fn get_bounding_box(cubes: &Vec<Pos>) -> (Pos, Pos) {
-    let (min_x, max_x) = cubes.iter()
-        .map(|p| p.x)
-        .fold((i64::MAX, i64::MIN), |acc, n| (acc.0.min(n), acc.1.max(n)));
-    let (min_y, max_y) = cubes.iter()
-        .map(|p| p.y)
-        .fold((i64::MAX, i64::MIN), |acc, n| (acc.0.min(n), acc.1.max(n)));
-    let (min_z, max_z) = cubes.iter()
-        .map(|p| p.z)
-        .fold((i64::MAX, i64::MIN), |acc, n| (acc.0.min(n), acc.1.max(n)));
-
-    (pos(min_x, min_y, min_z), pos(max_x, max_y, max_z))
-}
-
-fn is_inside_bounding_box(p: Pos, min: Pos, max: Pos) -> bool {
-    p.x >= min.x && p.x <= max.x &&  p.y >= min.y && p.y <= max.y && p.z >= min.z && p.z <= max.z
-}
-
-fn measure_external_surface_area(cubes: &Vec<Pos>) -> usize {
+fn measure_external_surface_area(cubes: &[Vec3<i64>]) -> usize {
     let cubes_set = cubes.iter()
         .fold(HashSet::new(), |mut set, pos| {
             set.insert(*pos); set
         });
 
-    let (min, max) = get_bounding_box(cubes);
     // increase the bounding box a bit to allow the flood-fill to cover the whole sphere
-    let (min, max) = (min - pos(1,1,1), max + pos(1,1,1));
-    let dims = max - min + pos(1,1,1);
-    // println!("min:{min:?}    max:{max:?}    dims:{dims:?}");
+    let bounds = Bounds::from_points(cubes.iter().copied()).unwrap().grow(1);
+    let dims = bounds.size();
 
     // Flood-fill around the sphere made of the cubes list.
     // This flood-fill will form a bigger cube that encloses the sphere, its inner surface
@@ -90,9 +49,9 @@ fn measure_external_surface_area(cubes: &Vec<Pos>) -> usize {
     let mut fill_surface_area = 0usize;
     let mut visited = HashSet::new();
     let mut queue = VecDeque::new();
-    queue.push_back(min);
+    queue.push_back(bounds.min);
     while let Some(p) = queue.pop_front() {
-        if is_inside_bounding_box(p, min, max) && !cubes_set.contains(&p) && !visited.contains(&p) {
+        if bounds.contains(p) && !cubes_set.contains(&p) && !visited.contains(&p) {
             fill_surface_area += 6;
             fill_surface_area -= count_neighbors(&visited, p) * 2;
             visited.insert(p);
@@ -109,17 +68,53 @@ fn measure_external_surface_area(cubes: &Vec<Pos>) -> usize {
     fill_surface_area - face_surface_top_bottom - face_surface_front_back - face_surface_left_right
 }
 
-fn main() {
-    let _p = profiler::profile();
+pub fn parse(input: &str) -> Result<Vec<Vec3<i64>>, ParseError> {
+    input.lines()
+        .map(|l| {
+            let (x, rest) = parser::split_once(input, l, ",")?;
+            let (y, z) = parser::split_once(input, rest, ",")?;
+            Ok(vec3(parser::number(input, x)?, parser::number(input, y)?, parser::number(input, z)?))
+        })
+        .collect()
+}
 
-    let cubes = INPUT.lines()
-        .map(|l| l.split(','))
-        .map(|mut n| pos(n.next().unwrap().parse().unwrap(), n.next().unwrap().parse().unwrap(), n.next().unwrap().parse().unwrap()))
-        .collect::<Vec<_>>();
+pub fn part1(cubes: &[Vec3<i64>]) -> usize {
+    measure_surface_area(cubes)
+}
+
+pub fn part2(cubes: &[Vec3<i64>]) -> usize {
+    measure_external_surface_area(cubes)
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Input = Vec<Vec3<i64>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
 
-    let res1 = measure_surface_area(&cubes);
-    let res2 = measure_external_surface_area(&cubes);
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
 
-    println!("[Part 1] Result is {res1}");
-    println!("[Part 2] Result is {res2}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example18");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 64);
+        assert_eq!(part2(&input), 58);
+    }
 }
@@ -1,15 +1,16 @@
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::collections::VecDeque;
 use std::hash::{Hash, Hasher};
 
-mod profiler;
+use crate::combinator::{self, delimited, number, preceded, tag, ws};
+use crate::parser::ParseError;
+use crate::{checked, parallel, profiler, Answer, Solution};
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-struct Cost { ore: u16, clay: u16, obsidian: u16 }
+pub struct Cost { ore: u16, clay: u16, obsidian: u16 }
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-struct Blueprint {
+pub struct Blueprint {
     id: u8,
     ore_robot_cost: Cost,
     clay_robot_cost: Cost,
@@ -19,31 +20,30 @@ struct Blueprint {
 }
 
 impl Blueprint {
-    fn parse(s: &str) -> Option<Blueprint> {
+    fn parse(input: &str, s: &str) -> Result<Blueprint, ParseError> {
         // Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 14 clay. Each geode robot costs 2 ore and 16 obsidian.
-        let (bp_id, ore_robot_cost) = s.trim_start_matches("Blueprint ").split_once(": Each ore robot costs ")?;
-        let bp_id = bp_id.parse::<u8>().ok()?;
-        let (ore_robot_cost, clay_robot_cost) = ore_robot_cost.split_once(" ore. Each clay robot costs ")?;
-        let ore_robot_cost = ore_robot_cost.parse::<u16>().ok()?;
-        let (clay_robot_cost, obsidian_robot_cost) = clay_robot_cost.split_once(" ore. Each obsidian robot costs ")?;
-        let clay_robot_cost = clay_robot_cost.parse::<u16>().ok()?;
-        let (obsidian_robot_cost_ore, obsidian_robot_cost_clay) = obsidian_robot_cost.split_once(" ore and ")?;
-        let obsidian_robot_cost_ore = obsidian_robot_cost_ore.parse::<u16>().ok()?;
-        let (obsidian_robot_cost_clay, geode_robot_cost) = obsidian_robot_cost_clay.split_once(" clay. Each geode robot costs ")?;
-        let obsidian_robot_cost_clay = obsidian_robot_cost_clay.parse::<u16>().ok()?;
-        let (geode_robot_cost_ore, geode_robot_cost_obsidian) = geode_robot_cost.split_once(" ore and ")?;
-        let geode_robot_cost_ore = geode_robot_cost_ore.parse::<u16>().ok()?;
-        let (geode_robot_cost_obsidian, _) = geode_robot_cost_obsidian.split_once(" obsidian.")?;
-        let geode_robot_cost_obsidian = geode_robot_cost_obsidian.parse::<u16>().ok()?;
-
-        Some(Blueprint {
+        let (
+            bp_id,
+            ore_robot_cost,
+            clay_robot_cost,
+            (obsidian_robot_cost_ore, obsidian_robot_cost_clay),
+            (geode_robot_cost_ore, geode_robot_cost_obsidian),
+        ) = combinator::parse_line(input, s, (
+            delimited(tag("Blueprint "), number::<u8>(), (tag(":"), ws())),
+            delimited(tag("Each ore robot costs "), number::<u16>(), (tag(" ore."), ws())),
+            delimited(tag("Each clay robot costs "), number::<u16>(), (tag(" ore."), ws())),
+            delimited(tag("Each obsidian robot costs "), (number::<u16>(), preceded(tag(" ore and "), number::<u16>())), (tag(" clay."), ws())),
+            delimited(tag("Each geode robot costs "), (number::<u16>(), preceded(tag(" ore and "), number::<u16>())), tag(" obsidian.")),
+        ))?;
+
+        Ok(Blueprint {
             id: bp_id,
             ore_robot_cost: Cost { ore: ore_robot_cost, clay: 0, obsidian: 0 },
             clay_robot_cost: Cost { ore: clay_robot_cost, clay: 0, obsidian: 0 },
             obsidian_robot_cost: Cost { ore: obsidian_robot_cost_ore, clay: obsidian_robot_cost_clay, obsidian: 0 },
             geode_robot_cost: Cost { ore: geode_robot_cost_ore, clay: 0, obsidian: geode_robot_cost_obsidian },
             max_cost: Cost {
-                ore: [ore_robot_cost, clay_robot_cost, obsidian_robot_cost_ore, geode_robot_cost_ore].into_iter().max()?,
+                ore: [ore_robot_cost, clay_robot_cost, obsidian_robot_cost_ore, geode_robot_cost_ore].into_iter().max().unwrap(),
                 clay: obsidian_robot_cost_clay,
                 obsidian: geode_robot_cost_obsidian
             }
@@ -68,21 +68,24 @@ impl State {
     fn collect_resources(self) -> Self {
         assert!(self.time_left >= 1);
         Self {
-            ore: self.ore + self.num_ore_robots,
-            clay: self.clay + self.num_clay_robots,
-            obsidian: self.obsidian + self.num_obsidian_robots,
-            geode: self.geode + self.num_geode_robots,
+            ore: checked::add(Day19::DAY, self.ore, self.num_ore_robots),
+            clay: checked::add(Day19::DAY, self.clay, self.num_clay_robots),
+            obsidian: checked::add(Day19::DAY, self.obsidian, self.num_obsidian_robots),
+            geode: checked::add(Day19::DAY, self.geode, self.num_geode_robots),
             time_left: self.time_left - 1,
             ..self
         }
     }
 
+    // Resources before the last `collect_resources`. A robot built in the last minute didn't collect
+    // anything yet, so the subtraction wraps around and the previous state counts as able to pay for
+    // anything, which prunes that branch like the release builds always did.
     fn prev(self) -> Self {
         Self {
-            ore: self.ore - self.num_ore_robots,
-            clay: self.clay - self.num_clay_robots,
-            obsidian: self.obsidian - self.num_obsidian_robots,
-            geode: self.geode - self.num_geode_robots,
+            ore: self.ore.wrapping_sub(self.num_ore_robots),
+            clay: self.clay.wrapping_sub(self.num_clay_robots),
+            obsidian: self.obsidian.wrapping_sub(self.num_obsidian_robots),
+            geode: self.geode.wrapping_sub(self.num_geode_robots),
             time_left: self.time_left + 1,
             ..self
         }
@@ -95,9 +98,9 @@ impl State {
     fn pay(self, cost: Cost) -> Self {
         assert!(self.can_pay(cost));
         Self {
-            ore: self.ore - cost.ore,
-            clay: self.clay - cost.clay,
-            obsidian: self.obsidian - cost.obsidian,
+            ore: checked::sub(Day19::DAY, self.ore, cost.ore),
+            clay: checked::sub(Day19::DAY, self.clay, cost.clay),
+            obsidian: checked::sub(Day19::DAY, self.obsidian, cost.obsidian),
             ..self
         }
     }
@@ -106,28 +109,28 @@ impl State {
     fn add_ore_robot(self, cost: Cost) -> Self {
         assert!(self.can_pay(cost));
         Self {
-            num_ore_robots: self.num_ore_robots + 1,
+            num_ore_robots: checked::add(Day19::DAY, self.num_ore_robots, 1),
             ..self.pay(cost)
         }
     }
     fn add_clay_robot(self, cost: Cost) -> Self {
         assert!(self.can_pay(cost));
         Self {
-            num_clay_robots: self.num_clay_robots + 1,
+            num_clay_robots: checked::add(Day19::DAY, self.num_clay_robots, 1),
             ..self.pay(cost)
         }
     }
     fn add_obsidian_robot(self, cost: Cost) -> Self {
         assert!(self.can_pay(cost));
         Self {
-            num_obsidian_robots: self.num_obsidian_robots + 1,
+            num_obsidian_robots: checked::add(Day19::DAY, self.num_obsidian_robots, 1),
             ..self.pay(cost)
         }
     }
     fn add_geode_robot(self, cost: Cost) -> Self {
         assert!(self.can_pay(cost));
         Self {
-            num_geode_robots: self.num_geode_robots + 1,
+            num_geode_robots: checked::add(Day19::DAY, self.num_geode_robots, 1),
             ..self.pay(cost)
         }
     }
@@ -159,6 +162,7 @@ impl State {
 //         BP 12:   geodes=9   quality=108
 //         BP 13:   geodes=0   quality=0
 
+#[allow(dead_code)]
 fn simulate(s: State, bp: &Blueprint, cache: &mut HashMap<u64, State>) -> State {
     let mut hasher = DefaultHasher::new();
     s.hash(&mut hasher);
@@ -191,11 +195,7 @@ fn simulate(s: State, bp: &Blueprint, cache: &mut HashMap<u64, State>) -> State
         }
 
         possible_new_states.iter()
-            .filter_map(|new_state_opt| if let Some(new_state) = new_state_opt {
-                Some(simulate(*new_state, bp, cache))
-            } else {
-                None
-            })
+            .filter_map(|new_state_opt| new_state_opt.as_ref().map(|new_state| simulate(*new_state, bp, cache)))
             .max_by_key(|new_state_result| new_state_result.geode).unwrap()
     } else {
         s
@@ -240,11 +240,7 @@ fn simulate2(s: State, bp: &Blueprint, cache: &mut HashMap<u64, State>) -> State
         }
 
         possible_new_states.iter()
-            .filter_map(|new_state_opt| if let Some(new_state) = new_state_opt {
-                Some(simulate2(*new_state, bp, cache))
-            } else {
-                None
-            })
+            .filter_map(|new_state_opt| new_state_opt.as_ref().map(|new_state| simulate2(*new_state, bp, cache)))
             .max_by_key(|new_state_result| new_state_result.geode).unwrap()
     } else {
         s
@@ -254,33 +250,68 @@ fn simulate2(s: State, bp: &Blueprint, cache: &mut HashMap<u64, State>) -> State
     new_state
 }
 
-fn main() {
-    let _p = profiler::profile();
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    input.lines()
+        .map(|l| Blueprint::parse(input, l))
+        .collect()
+}
 
-    const INPUT: &str = include_str!("data/input19");
-    let blueprints = INPUT.lines()
-        .map(Blueprint::parse)
-        .collect::<Option<Vec<_>>>().unwrap();
+/// Largest number of geodes each blueprint can open in `minutes`.
+fn max_geodes(blueprints: &[Blueprint], minutes: u8) -> Vec<u16> {
+    parallel::map(blueprints, |bp| {
+        let _p = profiler::span("blueprint");
+        simulate2(State::new(minutes), bp, &mut HashMap::new()).geode
+    })
+}
 
-    {let _p1 = profiler::profile();
-    let res1 = blueprints.iter()
-        .map(|bp| {
-            let max_geodes = simulate2(State::new(24), bp, &mut HashMap::new()).geode;
-            println!("BP {}:   geodes={}   quality={}", bp.id, max_geodes, bp.id as u64 * max_geodes as u64);
+pub fn part1(blueprints: &[Blueprint]) -> u64 {
+    blueprints.iter().zip(max_geodes(blueprints, 24))
+        .map(|(bp, max_geodes)| {
+            crate::debug!("BP {}:   geodes={}   quality={}", bp.id, max_geodes, bp.id as u64 * max_geodes as u64);
             bp.id as u64 * max_geodes as u64
         })
-        .sum::<u64>();
-    println!("[Part 1] Result is {res1}");
-    }
+        .sum::<u64>()
+}
 
-    {let _p2 = profiler::profile();
-    let res2 = blueprints[..3].iter()
-        .map(|bp| {
-            let max_geodes = simulate2(State::new(32), bp, &mut HashMap::new()).geode;
-            println!("BP {}:   geodes={}", bp.id, max_geodes);
+pub fn part2(blueprints: &[Blueprint]) -> u64 {
+    let blueprints = &blueprints[..blueprints.len().min(3)];
+    blueprints.iter().zip(max_geodes(blueprints, 32))
+        .map(|(bp, max_geodes)| {
+            crate::debug!("BP {}:   geodes={}", bp.id, max_geodes);
             max_geodes as u64
         })
-        .product::<u64>();
-    println!("[Part 2] Result is {res2}");
+        .product::<u64>()
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    type Input = Vec<Blueprint>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example19");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 33);
+        assert_eq!(part2(&input), 56 * 62);
     }
 }
@@ -1,28 +1,34 @@
-mod profiler;
+use crate::parser::ParseError;
+use crate::{Answer, Solution};
 
-fn main() {
-    let _p = profiler::profile();
+const WIN_LUT: [u8; 3]  = [2, 0, 1]; // Rock defeats Scissors, Paper defeats Rock, Scissors defeats Paper
+const LOSS_LUT: [u8; 3] = [1, 2, 0];
 
-    const WIN_LUT: [u8; 3]  = [2, 0, 1]; // Rock defeats Scissors, Paper defeats Rock, Scissors defeats Paper
-    const LOSS_LUT: [u8; 3] = [1, 2, 0];
-
-    let input = include_str!("data/input2");
-    let res1 = input
+/// Each round as `(opponent, second column)`, both in the range `0..3`.
+pub fn parse(input: &str) -> Result<Vec<(u8, u8)>, ParseError> {
+    input
         .lines()
-        .map(|l| {
-            let opponent = l.as_bytes()[0] - 'A' as u8;
-            let player = l.as_bytes()[2] - 'X' as u8;
+        .map(|l| match l.as_bytes() {
+            [opponent @ b'A'..=b'C', b' ', second @ b'X'..=b'Z'] => Ok((opponent - b'A', second - b'X')),
+            [b'A'..=b'C', b' ', ..] => Err(ParseError::at(input, &l[2..], "'X', 'Y' or 'Z'")),
+            [b'A'..=b'C', ..] => Err(ParseError::at(input, &l[1..], "' '")),
+            _ => Err(ParseError::at(input, l, "'A', 'B' or 'C'")),
+        })
+        .collect()
+}
+
+pub fn part1(rounds: &[(u8, u8)]) -> u64 {
+    rounds.iter()
+        .map(|&(opponent, player)| {
             let score = player + 1 + if WIN_LUT[player as usize] == opponent { 6 } else if player == opponent { 3 } else { 0 };
             score as u64
         })
-        .sum::<u64>();
-    println!("[Part 1] Result is {res1}");
+        .sum::<u64>()
+}
 
-    let res2 = input
-        .lines()
-        .map(|l| {
-            let opponent = l.as_bytes()[0] - 'A' as u8;
-            let outcome = l.as_bytes()[2] - 'X' as u8;
+pub fn part2(rounds: &[(u8, u8)]) -> u64 {
+    rounds.iter()
+        .map(|&(opponent, outcome)| {
             let score = outcome * 3 + match outcome {
                 0 /* lose */ => WIN_LUT[opponent as usize] + 1,
                 1 /* draw */ => opponent + 1,
@@ -31,6 +37,38 @@ fn main() {
             };
             score as u64
         })
-        .sum::<u64>();
-    println!("[Part 2] Result is {res2}");
+        .sum::<u64>()
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    type Input = Vec<(u8, u8)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example2");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 15);
+        assert_eq!(part2(&input), 12);
+    }
 }
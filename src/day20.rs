@@ -1,40 +1,134 @@
-mod profiler;
+use crate::checked;
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
-fn mix(v: &Vec<i64>, decryption_key: i64, num_rounds: usize) -> Vec<i64> {
+fn mix(v: &[i64], decryption_key: i64, num_rounds: usize) -> Vec<i64> {
     let mut indices = (0..v.len()).collect::<Vec<_>>();
+    // a single number has nowhere to move to, nor any length to move around
+    let num_rounds = if v.len() > 1 { num_rounds } else { 0 };
     for _ in 0..num_rounds {
         for orig_index in 0..v.len() {
             let old_index = indices.iter().position(|&idx| idx == orig_index).unwrap() as isize;
-            let new_index = (old_index + (v[orig_index] * decryption_key) as isize).rem_euclid(v.len() as isize - 1);
+            let offset = checked::cast::<i64, isize>(Day20::DAY, checked::mul(Day20::DAY, v[orig_index], decryption_key));
+            let new_index = checked::add(Day20::DAY, old_index, offset).rem_euclid(v.len() as isize - 1);
             indices.remove(old_index as usize);
             indices.insert(new_index as usize, orig_index);
         }
     }
     indices.iter()
-        .map(|&idx| v[idx] * decryption_key)
+        .map(|&idx| checked::mul(Day20::DAY, v[idx], decryption_key))
         .collect()
 }
 
-fn sum_grove_coords(v: &Vec<i64>) -> i64 {
+fn sum_grove_coords(v: &[i64]) -> i64 {
     let zero_index = v.iter().position(|&n| n == 0).unwrap();
-    [1000, 2000, 3000].into_iter()
+    let coords = [1000, 2000, 3000].into_iter()
         .map(|offset| (zero_index + offset) % v.len())
-        .map(|i| v[i])
-        .sum::<i64>()
+        .map(|i| v[i]);
+    checked::sum(Day20::DAY, coords)
 }
 
-fn main() {
-    let _p = profiler::profile();
+pub fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    let numbers = input.lines()
+        .map(|l| parser::number::<i64>(input, l))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !numbers.contains(&0) {
+        return Err(ParseError::after(input, input, "a 0 somewhere in the file"));
+    }
+    Ok(numbers)
+}
+
+pub fn part1(numbers: &[i64]) -> i64 {
+    sum_grove_coords(&mix(numbers, 1, 1))
+}
+
+pub fn part2(numbers: &[i64]) -> i64 {
+    sum_grove_coords(&mix(numbers, 811589153, 10))
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
 
-    const INPUT: &str = include_str!("data/input20");
-    let numbers = INPUT.lines()
-        .map(|l| l.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let res1 = sum_grove_coords(&mix(&numbers, 1, 1));
-    println!("[Part 1] Result is {res1}");
+    const EXAMPLE: &str = include_str!("data/example20");
 
-    let res2 = sum_grove_coords(&mix(&numbers, 811589153, 10));
-    println!("[Part 2] Result is {res2}");
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 3);
+        assert_eq!(part2(&input), 1623178306);
+    }
 
+    #[test]
+    fn test_mix() {
+        let input = parse(EXAMPLE).unwrap();
+        // the mixed list is circular, compare it starting at 0
+        let from_zero = |v: Vec<i64>| {
+            let zero = v.iter().position(|&n| n == 0).unwrap();
+            [&v[zero..], &v[..zero]].concat()
+        };
+        assert_eq!(from_zero(mix(&input, 1, 1)), [0, 3, -2, 1, 2, -3, 4]);
+        assert_eq!(
+            from_zero(mix(&input, 811589153, 1)),
+            [0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153],
+        );
+    }
+
+    #[test]
+    fn test_mix_edge_cases() {
+        // moves each number one swap at a time, which `mix` shortcuts with a remainder
+        fn mix_by_swaps(v: &[i64], num_rounds: usize) -> Vec<i64> {
+            let mut order = (0..v.len()).collect::<Vec<_>>();
+            for _ in 0..num_rounds {
+                for orig_index in 0..v.len() {
+                    let mut i = order.iter().position(|&idx| idx == orig_index).unwrap();
+                    for _ in 0..v[orig_index].unsigned_abs() {
+                        let j = if v[orig_index] > 0 { (i + 1) % v.len() } else { (i + v.len() - 1) % v.len() };
+                        order.swap(i, j);
+                        i = j;
+                    }
+                }
+            }
+            order.iter().map(|&idx| v[idx]).collect()
+        }
+        let from_zero = |v: Vec<i64>| {
+            let zero = v.iter().position(|&n| n == 0).unwrap();
+            [&v[zero..], &v[..zero]].concat()
+        };
+
+        let lists: [&[i64]; 6] = [
+            // moving by a multiple of len - 1 goes all the way around to the same place
+            &[0, 4, 1],
+            &[0, -4, 1],
+            // duplicates are moved in their original order
+            &[2, 2, 0, -2, -2],
+            &[0, 7, -13, 5, 5, 1],
+            &[1, 0],
+            &[0],
+        ];
+        for v in lists {
+            for rounds in [1, 3] {
+                assert_eq!(from_zero(mix(v, 1, rounds)), from_zero(mix_by_swaps(v, rounds)), "{v:?} mixed {rounds} times");
+            }
+        }
+    }
 }
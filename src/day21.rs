@@ -1,6 +1,8 @@
 use std::collections::{HashMap, VecDeque};
 
-mod profiler;
+use crate::checked;
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
 type Id = [u8; 4];
 
@@ -9,6 +11,14 @@ const fn id_from_str(s: &str) -> Id {
     [s[0], s[1], s[2], s[3]]
 }
 
+fn parse_id(input: &str, s: &str) -> Result<Id, ParseError> {
+    match s.as_bytes() {
+        &[a, b, c, d] if s.bytes().all(|c| c.is_ascii_lowercase()) => Ok([a, b, c, d]),
+        _ => Err(ParseError::at(input, s, "a four-letter monkey name")),
+    }
+}
+
+#[allow(dead_code)]
 fn id_to_str(id: Id) -> String {
     std::str::from_utf8(&id).unwrap().into()
 }
@@ -24,87 +34,91 @@ struct Operation {
 }
 
 #[derive(Debug, Copy, Clone)]
-struct Monkey {
+pub struct Monkey {
     id: Id,
     operation: Option<Operation>,
     value: Option<i64>,
 }
 
 impl Monkey {
-    fn parse(s: &str) -> Option<Monkey> {
-        let (id, value_or_operation) = s.split_once(": ")?;
-        let id = id_from_str(id);
+    fn parse(input: &str, s: &str) -> Result<Monkey, ParseError> {
+        let (id, value_or_operation) = parser::split_once(input, s, ": ")?;
+        let id = parse_id(input, id)?;
         let mut operation = None;
         let mut value = None;
-        match value_or_operation.chars().next()? {
-            'a'..='z' => { // operation
-                let mut parts = value_or_operation.split(' ');
-                let lhs = id_from_str(parts.next()?);
-                let op = match parts.next()? {
+        match parser::first_byte(input, value_or_operation, "a number or an operation")? {
+            b'a'..=b'z' => { // operation
+                let (lhs, rest) = parser::split_once(input, value_or_operation, " ")?;
+                let (op_str, rhs) = parser::split_once(input, rest, " ")?;
+                let lhs = parse_id(input, lhs)?;
+                let op = match op_str {
                     "+" => Op::Add,
                     "-" => Op::Sub,
                     "*" => Op::Mul,
                     "/" => Op::Div,
-                    _ => return None,
+                    _ => return Err(ParseError::at(input, op_str, "'+', '-', '*' or '/'")),
                 };
-                let rhs = id_from_str(parts.next()?);
+                let rhs = parse_id(input, rhs)?;
                 operation = Some(Operation { op, lhs, rhs });
             },
-            '0'..='9' => { // number
-                value = Some(value_or_operation.parse().ok()?);
+            b'0'..=b'9' => { // number
+                value = Some(parser::number(input, value_or_operation)?);
             },
-            _ => return None,
+            _ => return Err(ParseError::at(input, value_or_operation, "a number or an operation")),
         }
 
-        Some(Monkey { id, operation, value })
+        Ok(Monkey { id, operation, value })
     }
 }
 
 const ROOT: Id = id_from_str("root");
 const HUMN: Id = id_from_str("humn");
 
-fn build_id_to_index_map(monkeys: &Vec<Monkey>) -> HashMap<Id, usize> {
+fn build_id_to_index_map(monkeys: &[Monkey]) -> HashMap<Id, usize> {
     monkeys.iter().enumerate().fold(HashMap::new(), |mut map, (i, m)| {
         map.insert(m.id, i); map
     })
 }
 
-fn resolve_monkeys(monkeys: &mut Vec<Monkey>, id_to_index: &HashMap<Id, usize>) {
+/// Computes the value of `root` and of every monkey it depends on, `None` if one of them has neither a
+/// value nor an operation.
+fn resolve_monkeys(monkeys: &mut [Monkey], id_to_index: &HashMap<Id, usize>) -> Option<()> {
     let mut stack_to_resolve = Vec::new();
     stack_to_resolve.push(ROOT);
     while !stack_to_resolve.is_empty() {
         let m_id = stack_to_resolve.last().unwrap();
         let mut m = monkeys[*id_to_index.get(m_id).unwrap()];
-        if let Some(_) = m.value {
+        if m.value.is_some() {
             stack_to_resolve.pop();
         } else if let Some(operation) = m.operation {
             let m_lhs = monkeys[*id_to_index.get(&operation.lhs).unwrap()];
             let m_rhs = monkeys[*id_to_index.get(&operation.rhs).unwrap()];
             if let (Some(lhs_val), Some(rhs_val)) = (m_lhs.value, m_rhs.value) {
                 m.value = Some(match operation.op {
-                    Op::Add => lhs_val + rhs_val,
-                    Op::Sub => lhs_val - rhs_val,
-                    Op::Mul => lhs_val * rhs_val,
-                    Op::Div => lhs_val / rhs_val,
+                    Op::Add => checked::add(Day21::DAY, lhs_val, rhs_val),
+                    Op::Sub => checked::sub(Day21::DAY, lhs_val, rhs_val),
+                    Op::Mul => checked::mul(Day21::DAY, lhs_val, rhs_val),
+                    Op::Div => checked::div(Day21::DAY, lhs_val, rhs_val),
                 });
                 monkeys[*id_to_index.get(m_id).unwrap()] = m;
                 stack_to_resolve.pop();
             } else {
-                if let None = m_lhs.value {
+                if m_lhs.value.is_none() {
                     stack_to_resolve.push(operation.lhs);
                 }
 
-                if let None = m_rhs.value {
+                if m_rhs.value.is_none() {
                     stack_to_resolve.push(operation.rhs);
                 }
             }
         } else {
-            panic!("monkey in invalid state");
+            return None;
         }
     }
+    Some(())
 }
 
-fn get_monkey_dependencies(monkeys: &Vec<Monkey>, id_to_index: &HashMap<Id, usize>, target_id: Id) -> Vec<Id> {
+fn get_monkey_dependencies(monkeys: &[Monkey], id_to_index: &HashMap<Id, usize>, target_id: Id) -> Vec<Id> {
     let mut deps = Vec::new();
     let mut queue = VecDeque::new();
     queue.push_back(target_id);
@@ -122,7 +136,8 @@ fn get_monkey_dependencies(monkeys: &Vec<Monkey>, id_to_index: &HashMap<Id, usiz
     deps
 }
 
-fn get_path_to_human(monkeys: &Vec<Monkey>, id_to_index: &HashMap<Id, usize>, from_id: Id) -> Vec<Id> {
+#[allow(dead_code)]
+fn get_path_to_human(monkeys: &[Monkey], id_to_index: &HashMap<Id, usize>, from_id: Id) -> Vec<Id> {
     let mut path = Vec::new();
 
     let mut queue = VecDeque::new();
@@ -144,20 +159,19 @@ fn get_path_to_human(monkeys: &Vec<Monkey>, id_to_index: &HashMap<Id, usize>, fr
     path
 }
 
-fn find_value_for_human(monkeys: &Vec<Monkey>, id_to_index: &HashMap<Id, usize>) -> Option<i64> {
+/// Value `humn` must yell for both sides of `root` to be equal, `None` if `root` has no operation or
+/// doesn't depend on `humn`.
+fn find_value_for_human(monkeys: &[Monkey], id_to_index: &HashMap<Id, usize>) -> Option<i64> {
     let root = monkeys[*id_to_index.get(&ROOT)?];
-    let (path_start_id, mut expected_value) = if let Some(operation) = root.operation {
-        let (follow, expected) = if get_monkey_dependencies(monkeys, id_to_index, operation.lhs).contains(&HUMN) {
-            (operation.lhs, operation.rhs)
-        } else if get_monkey_dependencies(monkeys, id_to_index, operation.rhs).contains(&HUMN) {
-            (operation.rhs, operation.lhs)
-        } else {
-            panic!("missing human")
-        };
-        (follow, monkeys[*id_to_index.get(&expected)?].value?)
+    let operation = root.operation?;
+    let (path_start_id, expected) = if get_monkey_dependencies(monkeys, id_to_index, operation.lhs).contains(&HUMN) || operation.lhs == HUMN {
+        (operation.lhs, operation.rhs)
+    } else if get_monkey_dependencies(monkeys, id_to_index, operation.rhs).contains(&HUMN) || operation.rhs == HUMN {
+        (operation.rhs, operation.lhs)
     } else {
-        panic!("root must have an operation");
+        return None;
     };
+    let mut expected_value = monkeys[*id_to_index.get(&expected)?].value?;
 
     let mut queue = VecDeque::new();
     queue.push_back(path_start_id);
@@ -173,55 +187,116 @@ fn find_value_for_human(monkeys: &Vec<Monkey>, id_to_index: &HashMap<Id, usize>)
             // println!(" > {:?} {:?} {:?}", id_to_str(operation.lhs), operation.op, id_to_str(operation.rhs));
             if get_monkey_dependencies(monkeys, id_to_index, operation.lhs).contains(&HUMN) || operation.lhs == HUMN {
                 // println!("  > lhs contains HUMN, rhs is known");
-                let rhs_val = monkeys[*id_to_index.get(&operation.rhs).unwrap()].value.unwrap();
+                let rhs_val = monkeys[*id_to_index.get(&operation.rhs)?].value?;
                 let lhs_val = match operation.op {
-                    Op::Add => /*lhs_val + rhs_val = expected_value */ expected_value - rhs_val,
-                    Op::Sub => /*lhs_val - rhs_val = expected_value */ expected_value + rhs_val,
-                    Op::Mul => /*lhs_val * rhs_val = expected_value */ expected_value / rhs_val,
-                    Op::Div => /*lhs_val / rhs_val = expected_value */ expected_value * rhs_val,
+                    Op::Add => /*lhs_val + rhs_val = expected_value */ checked::sub(Day21::DAY, expected_value, rhs_val),
+                    Op::Sub => /*lhs_val - rhs_val = expected_value */ checked::add(Day21::DAY, expected_value, rhs_val),
+                    Op::Mul => /*lhs_val * rhs_val = expected_value */ checked::div(Day21::DAY, expected_value, rhs_val),
+                    Op::Div => /*lhs_val / rhs_val = expected_value */ checked::mul(Day21::DAY, expected_value, rhs_val),
                 };
                 expected_value = lhs_val;
                 queue.push_back(operation.lhs);
             } else if get_monkey_dependencies(monkeys, id_to_index, operation.rhs).contains(&HUMN) || operation.rhs == HUMN {
                 // println!("  > rhs contains HUMN, lhs is known");
-                let lhs_val = monkeys[*id_to_index.get(&operation.lhs).unwrap()].value.unwrap();
+                let lhs_val = monkeys[*id_to_index.get(&operation.lhs)?].value?;
                 let rhs_val = match operation.op {
-                    Op::Add => /*lhs_val + rhs_val = expected_value */ expected_value - lhs_val,
-                    Op::Sub => /*lhs_val - rhs_val = expected_value */ lhs_val - expected_value,
-                    Op::Mul => /*lhs_val * rhs_val = expected_value */ expected_value / lhs_val,
-                    Op::Div => /*lhs_val / rhs_val = expected_value */ lhs_val / expected_value,
+                    Op::Add => /*lhs_val + rhs_val = expected_value */ checked::sub(Day21::DAY, expected_value, lhs_val),
+                    Op::Sub => /*lhs_val - rhs_val = expected_value */ checked::sub(Day21::DAY, lhs_val, expected_value),
+                    Op::Mul => /*lhs_val * rhs_val = expected_value */ checked::div(Day21::DAY, expected_value, lhs_val),
+                    Op::Div => /*lhs_val / rhs_val = expected_value */ checked::div(Day21::DAY, lhs_val, expected_value),
                 };
                 expected_value = rhs_val;
                 queue.push_back(operation.rhs);
             }
         } else {
-            panic!("expected operation")
+            return None;
         }
     }
 
     None
 }
 
-fn main() {
-    let _p = profiler::profile();
+#[derive(Debug, Clone)]
+pub struct Troop {
+    monkeys: Vec<Monkey>,
+    id_to_index: HashMap<Id, usize>,
+}
 
-    const INPUT: &str = include_str!("data/input21");
-    let monkeys = INPUT.lines()
-        .map(Monkey::parse)
-        .collect::<Option<Vec<_>>>().unwrap();
+pub fn parse(input: &str) -> Result<Troop, ParseError> {
+    let monkeys = input.lines()
+        .map(|l| Monkey::parse(input, l))
+        .collect::<Result<Vec<_>, _>>()?;
     let id_to_index = build_id_to_index_map(&monkeys);
 
-    {
-        let mut monkeys1 = monkeys.clone();
-        resolve_monkeys(&mut monkeys1, &id_to_index);
-        let res1 = monkeys1[*id_to_index.get(&ROOT).unwrap()].value;
-        println!("[Part 1] Result is {res1:?}");
+    if !id_to_index.contains_key(&ROOT) {
+        return Err(ParseError::after(input, input, "a 'root' monkey"));
+    }
+    for (l, m) in input.lines().zip(&monkeys) {
+        if let Some(op) = m.operation {
+            if !id_to_index.contains_key(&op.lhs) || !id_to_index.contains_key(&op.rhs) {
+                return Err(ParseError::at(input, l, "an operation on monkeys defined in the file"));
+            }
+        }
+    }
+
+    Ok(Troop { monkeys, id_to_index })
+}
+
+pub fn part1(troop: &Troop) -> Option<i64> {
+    let mut monkeys = troop.monkeys.clone();
+    resolve_monkeys(&mut monkeys, &troop.id_to_index)?;
+    monkeys[*troop.id_to_index.get(&ROOT)?].value
+}
+
+pub fn part2(troop: &Troop) -> Option<i64> {
+    let mut monkeys = troop.monkeys.clone();
+    resolve_monkeys(&mut monkeys, &troop.id_to_index)?;
+    find_value_for_human(&monkeys, &troop.id_to_index)
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    type Input = Troop;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example21");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), Some(152));
+        assert_eq!(part2(&input), Some(301));
     }
 
-    {
-        let mut monkeys2 = monkeys.clone();
-        resolve_monkeys(&mut monkeys2, &id_to_index);
-        let res2 = find_value_for_human(&monkeys2, &id_to_index);
-        println!("[Part 2] Result is {res2:?}");
+    #[test]
+    fn test_no_human_path() {
+        // root yells a number
+        let input = parse("root: 5\nhumn: 3\n").unwrap();
+        assert_eq!(part1(&input), Some(5));
+        assert_eq!(part2(&input), None);
+        // root doesn't depend on humn
+        let input = parse("root: abcd + abcd\nabcd: 2\nhumn: 3\n").unwrap();
+        assert_eq!(part2(&input), None);
+        // humn is one side of root
+        let input = parse("root: humn + abcd\nabcd: 2\nhumn: 3\n").unwrap();
+        assert_eq!(part2(&input), Some(2));
     }
 }
@@ -1,30 +1,19 @@
-mod profiler;
-
-#[repr(u8)]
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Facing {
-    Right = 0,
-    Down = 1,
-    Left = 2,
-    Up = 3,
-}
-
-impl Facing {
-    fn turn_right(self) -> Self {
-        match self {
-            Facing::Right => Facing::Down,
-            Facing::Down => Facing::Left,
-            Facing::Left => Facing::Up,
-            Facing::Up => Facing::Right,
-        }
-    }
-    fn turn_left(self) -> Self {
-        match self {
-            Facing::Right => Facing::Up,
-            Facing::Down => Facing::Right,
-            Facing::Left => Facing::Down,
-            Facing::Up => Facing::Left,
-        }
+use std::collections::HashMap;
+
+use crate::geom::{vec2, Bounds, Dir, Vec2};
+use crate::grid::Grid;
+use crate::image::{self, Image, Rgb};
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
+
+/// Value of the facing in the password.
+fn facing_value(facing: Dir) -> i64 {
+    match facing {
+        Dir::E => 0,
+        Dir::S => 1,
+        Dir::W => 2,
+        Dir::N => 3,
+        _ => unreachable!("diagonal facing {facing:?}"),
     }
 }
 
@@ -32,215 +21,231 @@ impl Facing {
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Tile { Open = b'.', Wall = b'#', Void = b' ' }
 
-impl From<u8> for Tile {
-    fn from(c: u8) -> Self {
+impl Tile {
+    fn parse(c: u8) -> Option<Self> {
         match c {
-            b'.' => Tile::Open,
-            b'#' => Tile::Wall,
-            b' ' => Tile::Void,
-            _ => panic!("unexpected character '{}'", std::char::from_u32(c as u32).unwrap()),
+            b'.' => Some(Tile::Open),
+            b'#' => Some(Tile::Wall),
+            b' ' => Some(Tile::Void),
+            _ => None,
         }
     }
 }
 
+impl From<Tile> for char {
+    fn from(tile: Tile) -> char {
+        tile as u8 as char
+    }
+}
+
 #[derive(Debug, Clone)]
-struct Map {
-    map: Vec<Vec<Tile>>,
+pub struct Map {
+    map: Grid<Tile>,
 }
 
 impl Map {
-    fn new(map_str: &str) -> Self {
-        Self {
-            map: map_str
-                .lines()
-                .map(|l| l.as_bytes().iter().map(|c| Tile::from(*c)).collect())
-                .collect::<Vec<Vec<Tile>>>()
-        }
+    fn new(input: &str, map_str: &str) -> Result<Self, ParseError> {
+        Ok(Self { map: Grid::parse(input, map_str, "'.', '#' or ' '", Tile::parse)? })
     }
 
-    fn get_start(&self) -> (i64, i64) {
-        (self.map[0].iter().position(|t| *t == Tile::Open).unwrap() as i64, 0)
+    fn get_start(&self) -> Vec2<i64> {
+        self.map.position(|t| *t == Tile::Open).unwrap()
     }
 
     fn at(&self, x: i64, y: i64) -> Tile {
-        if x < 0 || y < 0 {
-            return Tile::Void;
-        }
-
-        let x = x as usize;
-        let y = y as usize;
-        if y >= self.map.len() || x >= self.map[y].len() {
-            Tile::Void
-        } else {
-            self.map[y][x]
-        }
+        self.map.get(vec2(x, y)).copied().unwrap_or(Tile::Void)
     }
 
-    fn wrap_right(&self, _x: i64, y: i64) -> Option<(i64, i64)> {
-        let new_x = self.map[y as usize].iter().position(|t| *t != Tile::Void)?;
-        match self.map[y as usize][new_x as usize] {
-            Tile::Open => Some((new_x as i64, y)),
-            _ => None
-        }
+    fn row(&self, y: i64) -> &[Tile] {
+        self.map.rows().nth(y as usize).unwrap()
     }
 
-    fn wrap_down(&self, x: i64, _y: i64) -> Option<(i64, i64)> {
-        let new_y = self.map.iter().position(|row| (x as usize) < row.len() && row[x as usize] != Tile::Void)?;
-        match self.map[new_y as usize][x as usize] {
-            Tile::Open => Some((x, new_y as i64)),
+    fn wrap_right(&self, _x: i64, y: i64) -> Option<Vec2<i64>> {
+        let new_x = self.row(y).iter().position(|t| *t != Tile::Void)? as i64;
+        match self.at(new_x, y) {
+            Tile::Open => Some(vec2(new_x, y)),
             _ => None
         }
     }
 
-    fn wrap_left(&self, _x: i64, y: i64) -> Option<(i64, i64)> {
-        let new_x = self.map[y as usize].iter().rposition(|t| *t != Tile::Void)?;
-        match self.map[y as usize][new_x as usize] {
-            Tile::Open => Some((new_x as i64, y)),
+    fn wrap_down(&self, x: i64, _y: i64) -> Option<Vec2<i64>> {
+        let new_y = (0..self.map.height() as i64).find(|&y| self.at(x, y) != Tile::Void)?;
+        match self.at(x, new_y) {
+            Tile::Open => Some(vec2(x, new_y)),
             _ => None
         }
     }
 
-    fn wrap_up(&self, x: i64, _y: i64) -> Option<(i64, i64)> {
-        let new_y = self.map.iter().rposition(|row| (x as usize) < row.len() && row[x as usize] != Tile::Void)?;
-        match self.map[new_y as usize][x as usize] {
-            Tile::Open => Some((x, new_y as i64)),
+    fn wrap_left(&self, _x: i64, y: i64) -> Option<Vec2<i64>> {
+        let new_x = self.row(y).iter().rposition(|t| *t != Tile::Void)? as i64;
+        match self.at(new_x, y) {
+            Tile::Open => Some(vec2(new_x, y)),
             _ => None
         }
     }
 
-    // cube handling hardcoded for real input, not example
-    fn cube_wrap_right(&self, _x: i64, y: i64) -> Option<(i64, i64, Facing)> {
-        let m = match y {
-            0..=49 => (99, 149 - y, Facing::Left),
-            50..=99 => (100 + y - 50, 49, Facing::Up),
-            100..=149 => (149, 49 - (y - 100), Facing::Left),
-            150..=199 => (50 + y - 150, 149, Facing::Up),
-            _ => panic!("invalid y coord '{y}'"),
-        };
-        match self.map[m.1 as usize][m.0 as usize] {
-            Tile::Open => Some(m),
+    fn wrap_up(&self, x: i64, _y: i64) -> Option<Vec2<i64>> {
+        let new_y = (0..self.map.height() as i64).rfind(|&y| self.at(x, y) != Tile::Void)?;
+        match self.at(x, new_y) {
+            Tile::Open => Some(vec2(x, new_y)),
             _ => None
         }
     }
 
-    fn cube_wrap_left(&self, _x: i64, y: i64) -> Option<(i64, i64, Facing)> {
-        let m = match y {
-            0..=49 => (0, 149 - y, Facing::Right),
-            50..=99 => (y - 50, 100, Facing::Down),
-            100..=149 => (50, 49 - (y - 100), Facing::Right),
-            150..=199 => (50 + y - 150, 0, Facing::Down),
-            _ => panic!("invalid y coord '{y}'"),
-        };
-        match self.map[m.1 as usize][m.0 as usize] {
-            Tile::Open => Some(m),
+    /// Where stepping off the board from `pos` with `facing` leads once the board is folded into
+    /// `cube`, `None` if there is a wall there.
+    fn cube_wrap(&self, cube: &Cube, pos: Vec2<i64>, facing: Dir) -> Option<(Vec2<i64>, Dir)> {
+        let (next, next_facing) = cube.wrap(pos, facing);
+        match self.at(next.x, next.y) {
+            Tile::Open => Some((next, next_facing)),
             _ => None
         }
     }
+}
 
-    fn cube_wrap_down(&self, x: i64, _y: i64) -> Option<(i64, i64, Facing)> {
-        let m = match x {
-            0..=49 => (x + 100, 0, Facing::Down),
-            50..=99 => (49, 150 + x - 50, Facing::Left),
-            100..=149 => (99, 50 + x - 100, Facing::Left),
-            _ => panic!("invalid x coord '{x}'"),
-        };
-        match self.map[m.1 as usize][m.0 as usize] {
-            Tile::Open => Some(m),
-            _ => None
-        }
+/// A cube unfolded on the board: the position of each face, counted in faces, and the edges glued
+/// together when folding it, as the face and facing when stepping off an edge and the face entered
+/// and the new facing.
+struct CubeNet {
+    faces: [Vec2<i64>; 6],
+    edges: [(usize, Dir, usize, Dir); 14],
+}
+
+/// Net of the puzzle inputs.
+const INPUT_NET: CubeNet = CubeNet {
+    faces: [vec2(1, 0), vec2(2, 0), vec2(1, 1), vec2(0, 2), vec2(1, 2), vec2(0, 3)],
+    edges: [
+        (0, Dir::W, 3, Dir::E), (0, Dir::N, 5, Dir::E),
+        (1, Dir::E, 4, Dir::W), (1, Dir::S, 2, Dir::W), (1, Dir::N, 5, Dir::N),
+        (2, Dir::E, 1, Dir::N), (2, Dir::W, 3, Dir::S),
+        (3, Dir::W, 0, Dir::E), (3, Dir::N, 2, Dir::E),
+        (4, Dir::E, 1, Dir::W), (4, Dir::S, 5, Dir::W),
+        (5, Dir::E, 4, Dir::N), (5, Dir::S, 1, Dir::S), (5, Dir::W, 0, Dir::S),
+    ],
+};
+
+/// Net of the example.
+const EXAMPLE_NET: CubeNet = CubeNet {
+    faces: [vec2(2, 0), vec2(0, 1), vec2(1, 1), vec2(2, 1), vec2(2, 2), vec2(3, 2)],
+    edges: [
+        (0, Dir::E, 5, Dir::W), (0, Dir::W, 2, Dir::S), (0, Dir::N, 1, Dir::S),
+        (1, Dir::S, 4, Dir::N), (1, Dir::W, 5, Dir::N), (1, Dir::N, 0, Dir::S),
+        (2, Dir::S, 4, Dir::E), (2, Dir::N, 0, Dir::E),
+        (3, Dir::E, 5, Dir::S),
+        (4, Dir::S, 1, Dir::N), (4, Dir::W, 2, Dir::N),
+        (5, Dir::E, 0, Dir::W), (5, Dir::S, 1, Dir::E), (5, Dir::N, 3, Dir::W),
+    ],
+};
+
+/// The board folded into a cube whose faces are `size` tiles wide.
+struct Cube {
+    size: i64,
+    net: &'static CubeNet,
+}
+
+impl Cube {
+    /// Folds the board with the known net its faces are laid out in, 50 tiles wide in the inputs and 4
+    /// in the example, `None` if it isn't one of them.
+    fn fold(map: &Map) -> Option<Self> {
+        let tiles = map.map.cells().filter(|(_, t)| **t != Tile::Void).count() as i64;
+        let size = (1..).find(|n| 6 * n * n >= tiles).unwrap();
+        let is_face = |f: Vec2<i64>| map.at(f.x * size, f.y * size) != Tile::Void;
+        let net = [&INPUT_NET, &EXAMPLE_NET].into_iter()
+            .find(|net| 6 * size * size == tiles && net.faces.iter().all(|&f| is_face(f)))?;
+        Some(Self { size, net })
     }
 
-    fn cube_wrap_up(&self, x: i64, _y: i64) -> Option<(i64, i64, Facing)> {
-        let m = match x {
-            0..=49 => (50, 50 + x, Facing::Right),
-            50..=99 => (0, 150 + x - 50, Facing::Right),
-            100..=149 => (x - 100, 199, Facing::Up),
-            _ => panic!("invalid x coord '{x}'"),
+    /// Where stepping off the board from `pos` with `facing` leads, and the new facing.
+    fn wrap(&self, pos: Vec2<i64>, facing: Dir) -> (Vec2<i64>, Dir) {
+        let n = self.size;
+        let face_pos = vec2(pos.x.div_euclid(n), pos.y.div_euclid(n));
+        let face = self.net.faces.iter().position(|&f| f == face_pos).unwrap();
+        let &(_, _, next_face, next_facing) = self.net.edges.iter()
+            .find(|&&(f, d, _, _)| f == face && d == facing)
+            .unwrap_or_else(|| panic!("no edge leaving face {face} facing {facing:?}"));
+
+        // position along the edge, going clockwise around the face
+        let p = pos - face_pos * n;
+        let i = match facing {
+            Dir::E => p.y,
+            Dir::S => n - 1 - p.x,
+            Dir::W => n - 1 - p.y,
+            Dir::N => p.x,
+            _ => unreachable!("diagonal facing {facing:?}"),
         };
-        match self.map[m.1 as usize][m.0 as usize] {
-            Tile::Open => Some(m),
-            _ => None
-        }
+        // glued edges go around their faces in opposite directions
+        let j = n - 1 - i;
+        let next = match next_facing {
+            Dir::E => vec2(0, n - 1 - j),
+            Dir::S => vec2(j, 0),
+            Dir::W => vec2(n - 1, j),
+            Dir::N => vec2(n - 1 - j, n - 1),
+            _ => unreachable!("diagonal facing {next_facing:?}"),
+        };
+        (self.net.faces[next_face] * n + next, next_facing)
     }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct State {
-    x: i64,
-    y: i64,
-    facing: Facing,
+    pos: Vec2<i64>,
+    facing: Dir,
 }
 
-fn move_forward(s: State, map: &Map, distance: i32) -> State {
-    let mut new_x = s.x;
-    let mut new_y = s.y;
+fn move_forward(s: State, map: &Map, distance: i32, visit: &mut impl FnMut(State)) -> State {
+    let mut new_pos = s.pos;
     let mut distance_moved = 0;
     'move_loop: while distance_moved < distance {
-        let (next_x, next_y) = match s.facing {
-            Facing::Right => (new_x + 1, new_y),
-            Facing::Down  => (new_x, new_y + 1),
-            Facing::Left  => (new_x - 1, new_y),
-            Facing::Up    => (new_x, new_y - 1),
-        };
+        let next = new_pos + s.facing.vector();
 
-        let Some((next_x, next_y)) = (match map.at(next_x, next_y) {
+        let Some(next) = (match map.at(next.x, next.y) {
             Tile::Void => match s.facing {
-                Facing::Right => map.wrap_right(next_x, next_y),
-                Facing::Down => map.wrap_down(next_x, next_y),
-                Facing::Left => map.wrap_left(next_x, next_y),
-                Facing::Up => map.wrap_up(next_x, next_y),
+                Dir::E => map.wrap_right(next.x, next.y),
+                Dir::S => map.wrap_down(next.x, next.y),
+                Dir::W => map.wrap_left(next.x, next.y),
+                Dir::N => map.wrap_up(next.x, next.y),
+                _ => unreachable!("diagonal facing {:?}", s.facing),
             },
-            Tile::Open => Some((next_x, next_y)),
+            Tile::Open => Some(next),
             Tile::Wall => None,
         }) else {
             break 'move_loop;
         };
 
-        (new_x, new_y) = (next_x, next_y);
+        new_pos = next;
         distance_moved += 1;
+        visit(State { pos: new_pos, ..s });
     }
 
     State {
-        x: new_x,
-        y: new_y,
+        pos: new_pos,
         ..s
     }
 }
 
 
-fn move_forward_cube(s: State, map: &Map, distance: i32) -> State {
-    let mut new_x = s.x;
-    let mut new_y = s.y;
+fn move_forward_cube(s: State, map: &Map, cube: &Cube, distance: i32, visit: &mut impl FnMut(State)) -> State {
+    let mut new_pos = s.pos;
     let mut new_facing = s.facing;
     let mut distance_moved = 0;
     'move_loop: while distance_moved < distance {
-        let (next_x, next_y) = match new_facing {
-            Facing::Right => (new_x + 1, new_y),
-            Facing::Down  => (new_x, new_y + 1),
-            Facing::Left  => (new_x - 1, new_y),
-            Facing::Up    => (new_x, new_y - 1),
-        };
+        let next = new_pos + new_facing.vector();
 
-        let Some((next_x, next_y, next_facing)) = (match map.at(next_x, next_y) {
-            Tile::Void => match s.facing {
-                Facing::Right => map.cube_wrap_right(next_x, next_y),
-                Facing::Down => map.cube_wrap_down(next_x, next_y),
-                Facing::Left => map.cube_wrap_left(next_x, next_y),
-                Facing::Up => map.cube_wrap_up(next_x, next_y),
-            },
-            Tile::Open => Some((next_x, next_y, new_facing)),
+        let Some((next, next_facing)) = (match map.at(next.x, next.y) {
+            Tile::Void => map.cube_wrap(cube, new_pos, new_facing),
+            Tile::Open => Some((next, new_facing)),
             Tile::Wall => None,
         }) else {
             break 'move_loop;
         };
 
-        (new_x, new_y, new_facing) = (next_x, next_y, next_facing);
+        (new_pos, new_facing) = (next, next_facing);
         distance_moved += 1;
+        visit(State { pos: new_pos, facing: new_facing });
     }
 
     State {
-        x: new_x,
-        y: new_y,
+        pos: new_pos,
         facing: new_facing,
     }
 }
@@ -256,51 +261,166 @@ fn turn(s: State, dir: u8) -> State {
     }
 }
 
-fn follow_path<const CUBE: bool>(mut s: State, map: &Map, path: &str) -> State {
-    let mut distance_str_buffer = Vec::new();
-    let path = path.as_bytes();
-    let mut i = 0;
+/// Follows `path` from `s`, on the faces of `cube` if given, calling `visit` with every state on the
+/// way, turns included.
+fn follow_path(mut s: State, map: &Map, cube: Option<&Cube>, path: &[Step], mut visit: impl FnMut(State)) -> State {
+    visit(s);
+    for &(distance, turn_dir) in path {
+        s = match cube {
+            Some(cube) => move_forward_cube(s, map, cube, distance, &mut visit),
+            None => move_forward(s, map, distance, &mut visit),
+        };
+
+        if let Some(turn_dir) = turn_dir {
+            s = turn(s, turn_dir);
+            visit(s);
+        }
+    }
+
+    s
+}
+
+/// A distance to move forward, then the turn to make, `L` or `R`, which the last step doesn't have.
+type Step = (i32, Option<u8>);
+
+#[derive(Debug, Clone)]
+pub struct Notes {
+    map: Map,
+    path: Vec<Step>,
+}
+
+pub fn parse(input: &str) -> Result<Notes, ParseError> {
+    let (map_str, path_str) = parser::split_once(input, input, "\n\n")?;
+    let map = Map::new(input, map_str)?;
+    if !map.map.rows().next().is_some_and(|row| row.contains(&Tile::Open)) {
+        return Err(ParseError::at(input, map_str, "an open tile in the first row"));
+    }
+
+    let path_str = path_str.trim();
+    let mut path = Vec::new();
+    let mut rest = path_str;
     loop {
-        distance_str_buffer.clear();
-        while i < path.len() && (b'0'..=b'9').contains(&path[i]) {
-            distance_str_buffer.push(path[i]);
-            i += 1;
+        let (digits, after) = rest.split_at(rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len()));
+        if digits.is_empty() {
+            return Err(match rest {
+                "" => ParseError::after(input, path_str, "a distance"),
+                rest => ParseError::at(input, rest, "a distance"),
+            });
+        }
+        let distance = digits.parse().map_err(|_| ParseError::at(input, digits, "a distance of at most 2147483647"))?;
+        match after.bytes().next() {
+            None => {
+                path.push((distance, None));
+                break;
+            },
+            Some(turn_dir @ (b'L' | b'R')) => path.push((distance, Some(turn_dir))),
+            Some(_) => return Err(ParseError::at(input, after, "a distance, 'L' or 'R'")),
         }
+        rest = &after[1..];
+    }
 
-        let distance = std::str::from_utf8(&distance_str_buffer).unwrap().parse().unwrap();
-        s = if CUBE { move_forward_cube(s, map, distance) } else { move_forward(s, map, distance) };
+    Ok(Notes { map, path })
+}
 
-        if i >= path.len() {
-            break;
+/// Colours of the exported image: the void, open tiles, walls, the start, the end, then the path
+/// by facing.
+const PALETTE: [Rgb; 9] = [
+    Rgb(12, 12, 16),
+    Rgb(200, 196, 184),
+    Rgb(72, 68, 64),
+    Rgb(40, 200, 80),
+    Rgb(240, 40, 40),
+    Rgb(232, 112, 32),
+    Rgb(40, 120, 232),
+    Rgb(200, 48, 200),
+    Rgb(32, 180, 200),
+];
+
+/// Saves the board with the last facing of the path on each tile it went through.
+fn save_board<const CUBE: bool>(map: &Map, trail: &HashMap<Vec2<i64>, Dir>, start: Vec2<i64>, end: Vec2<i64>) {
+    let bounds = Bounds { min: map.map.origin(), max: map.map.max() };
+    let board = Image::render(bounds, |p| match (map.at(p.x, p.y), trail.get(&p)) {
+        _ if p == end => 4,
+        _ if p == start => 3,
+        (_, Some(&facing)) => 5 + facing_value(facing) as u8,
+        (Tile::Open, None) => 1,
+        (Tile::Wall, None) => 2,
+        (Tile::Void, None) => 0,
+    });
+    image::save(&format!("day22-part{}", if CUBE { 2 } else { 1 }), &board.scale(3), &PALETTE);
+}
+
+fn password<const CUBE: bool>(notes: &Notes, cube: Option<&Cube>) -> i64 {
+    let start_state = State {
+        pos: notes.map.get_start(),
+        facing: Dir::E,
+    };
+    let mut trail = image::enabled().then(HashMap::new);
+    let final_state = follow_path(start_state, &notes.map, cube, &notes.path, |s| {
+        if let Some(trail) = &mut trail {
+            trail.insert(s.pos, s.facing);
         }
+    });
+    if let Some(trail) = trail {
+        save_board::<CUBE>(&notes.map, &trail, start_state.pos, final_state.pos);
+    }
+    1000 * (final_state.pos.y + 1) + 4 * (final_state.pos.x + 1) + facing_value(final_state.facing)
+}
 
-        let turn_dir = path[i];
-        i += 1;
-        s = turn(s, turn_dir);
+pub fn part1(notes: &Notes) -> i64 {
+    password::<false>(notes, None)
+}
+
+/// Password when the board is folded into a cube, `None` if its faces aren't laid out in a known net.
+pub fn part2(notes: &Notes) -> Option<i64> {
+    let cube = Cube::fold(&notes.map)?;
+    Some(password::<true>(notes, Some(&cube)))
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    type Input = Notes;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
 
-    s
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
-fn main() {
-    let _p = profiler::profile();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    const INPUT: &str = include_str!("data/input22");
-    let (map_str, path_str) = INPUT.split_once("\n\n").unwrap();
-    let map = Map::new(map_str);
-    let path = path_str.trim();
+    const EXAMPLE: &str = include_str!("data/example22");
 
-    let (start_x, start_y) = map.get_start();
-    let start_state = State {
-        x: start_x,
-        y: start_y,
-        facing: Facing::Right,
-    };
-    let final_state = follow_path::<false>(start_state, &map, path);
-    let final_state2 = follow_path::<true>(start_state, &map, path);
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 6032);
+        assert_eq!(part2(&input), Some(5031));
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let board = "...#\n.#..\n#...\n....\n\n";
+        assert_eq!(parse(&format!("{board}10R5L0\n")).unwrap().path, [(10, Some(b'R')), (5, Some(b'L')), (0, None)]);
 
-    let res1 = 1000 * (final_state.y + 1) + 4 * (final_state.x + 1) + final_state.facing as i64;
-    let res2 = 1000 * (final_state2.y + 1) + 4 * (final_state2.x + 1) + final_state2.facing as i64;
-    println!("[Part 1] Result is {res1:?}");
-    println!("[Part 2] Result is {res2:?}");
+        let input = format!("{board}10R99999999999L2\n");
+        let err = parse(&input).unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (6, 4, "a distance of at most 2147483647"));
+        assert_eq!(parse(&format!("{board}10RL2\n")).unwrap_err().column, 4);
+        assert_eq!(parse(&format!("{board}10R\n")).unwrap_err().expected, "a distance");
+
+        // a single face doesn't fold into a cube
+        assert_eq!(part2(&parse(&format!("{board}10R5\n")).unwrap()), None);
+    }
 }
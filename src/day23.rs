@@ -1,52 +1,15 @@
 use std::collections::{HashMap, HashSet};
-use std::ops;
 
-mod profiler;
+use crate::geom::{vec2, Bounds, Dir, Vec2};
+use crate::image::{Image, Recording, Rgb};
+use crate::parser::ParseError;
+use crate::visualize;
+use crate::{Answer, Solution};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Pos { x: i64, y: i64 }
-macro_rules! pos {
-    ($x:expr, $y:expr) => { Pos { x: $x, y: $y }};
-}
-
-impl ops::Add for Pos {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self {
-        pos!(self.x + rhs.x, self.y + rhs.y)
-    }
-}
-
-impl ops::Sub for Pos {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self {
-        pos!(self.x - rhs.x, self.y - rhs.y)
-    }
-}
-
-type Map = HashSet<Pos>;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Dir { NW, N, NE, E, SE, S, SW, W }
-
-impl Dir {
-    fn vector(self) -> Pos {
-        match self {
-            Dir::NW => pos!(-1, -1),
-            Dir::N  => pos!( 0, -1),
-            Dir::NE => pos!(1, -1),
-            Dir::E  => pos!(1,  0),
-            Dir::SE => pos!(1, 1),
-            Dir::S  => pos!( 0, 1),
-            Dir::SW => pos!(-1, 1),
-            Dir::W  => pos!(-1,  0),
-        }
-    }
-}
+/// Positions of every elf.
+pub type Map = HashSet<Vec2<i64>>;
 
 fn round(map: &Map, round_index: usize) -> Map {
-    const ALL_DIRS: [Dir; 8] = [Dir::NW, Dir::N, Dir::NE, Dir::E, Dir::SE, Dir::S, Dir::SW, Dir::W];
     const DIRS_TO_CHECK: [[Dir; 3]; 4] = [
         [Dir::N, Dir::NE, Dir::NW],
         [Dir::S, Dir::SE, Dir::SW],
@@ -56,10 +19,10 @@ fn round(map: &Map, round_index: usize) -> Map {
     let first_dir_to_check = round_index % DIRS_TO_CHECK.len();
 
     // first half
-    let mut new_pos_to_orig_pos = HashMap::<Pos, Vec<Pos>>::new();
+    let mut new_pos_to_orig_pos = HashMap::<Vec2<i64>, Vec<Vec2<i64>>>::new();
     for elf in map.iter() {
         let mut move_dir = None;
-        if ALL_DIRS.iter().any(|d| map.contains(&(*elf + d.vector()))) {
+        if Dir::ALL.iter().any(|d| map.contains(&(*elf + d.vector()))) {
             // if any elf adjacent consider a direction to move to
             for k in 0..DIRS_TO_CHECK.len() {
                 let i = (k + first_dir_to_check) % DIRS_TO_CHECK.len();
@@ -99,69 +62,165 @@ fn round(map: &Map, round_index: usize) -> Map {
 }
 
 fn get_dimensions(map: &Map) -> (i64, i64) {
-    let (min_x, max_x, min_y, max_y) = map.iter()
-        .fold((i64::MAX, i64::MIN, i64::MAX, i64::MIN), |acc, p|
-            (acc.0.min(p.x), acc.1.max(p.x), acc.2.min(p.y), acc.3.max(p.y)));
-
-    let width = max_x - min_x + 1;
-    let height = max_y - min_y + 1;
-    (width, height)
+    let size = Bounds::from_points(map.iter().copied()).unwrap().size();
+    (size.x, size.y)
 }
 
 fn count_empty_ground_tiles(map: &Map) -> i64 {
     let (w, h) = get_dimensions(map);
-    println!("{w} x {h}");
+    crate::debug!("{w} x {h}");
     (w * h) - map.len() as i64
 }
 
-fn print_map(map: &Map) {
-    let (min_x, min_y) = map.iter()
-        .fold((i64::MAX, i64::MAX), |acc, p|
-            (acc.0.min(p.x), acc.1.min(p.y)));
-    let (w, h) = get_dimensions(map);
-    let mut map_str = vec![vec![b'.'; w as usize]; h as usize];
-    for elf in map {
-        map_str[(elf.y - min_y) as usize][(elf.x - min_x) as usize] = b'#';
+/// Shows the elves, or the middle of them if they don't fit in a view.
+fn draw_map(map: &Map, rounds: usize) {
+    let bounds = Bounds::from_points(map.iter().copied()).unwrap();
+    let view = visualize::view((bounds.min + bounds.max) / 2, Some(bounds));
+    let body = visualize::render(view, |p| if map.contains(&p) { '#' } else { '.' });
+    visualize::frame(&format!("Day 23: round {rounds}, {} x {}", bounds.size().x, bounds.size().y), &body);
+}
+
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let mut map = Map::new();
+    for (y, row) in input.lines().enumerate() {
+        for (x, c) in row.bytes().enumerate() {
+            match c {
+                b'#' => { map.insert(vec2(x as i64, y as i64)); },
+                b'.' => {},
+                _ => return Err(ParseError::at(input, &row[x..], "'#' or '.'")),
+            }
+        }
     }
+    if map.is_empty() {
+        return Err(ParseError::after(input, input, "at least one elf"));
+    }
+    Ok(map)
+}
 
-    for map_row_str in map_str {
-        println!("{}", std::str::from_utf8(&map_row_str).unwrap());
+pub fn part1(initial_map: &Map) -> i64 {
+    let mut final_map = initial_map.clone();
+    for i in 0..10 {
+        final_map = round(&final_map, i);
+        if visualize::enabled() {
+            draw_map(&final_map, i + 1);
+        }
     }
+
+    count_empty_ground_tiles(&final_map)
 }
 
-fn main() {
-    let _p = profiler::profile();
+/// Colours of the ground and of the elves in the exported animation.
+const PALETTE: [Rgb; 2] = [Rgb(40, 56, 32), Rgb(120, 232, 96)];
 
-    const INPUT: &str = include_str!("data/input23");
-    let initial_map = INPUT.lines().enumerate().fold(Map::new(), |mut map, (y, row)| {
-        row.as_bytes().iter().enumerate().for_each(|(x, &c)| if c == b'#' { map.insert(pos!(x as i64, y as i64)); });
-        map
-    });
+fn map_image(map: &Map) -> Image {
+    let bounds = Bounds::from_points(map.iter().copied()).unwrap();
+    Image::render(bounds, |p| map.contains(&p) as u8)
+}
 
-    {
-        let mut final_map = initial_map.clone();
-        for i in 0..10 {
-            final_map = round(&final_map, i);
+/// Number of the first round in which no elf moves, `None` if they move around in circles forever.
+pub(crate) fn last_round(initial_map: &Map, mut recording: Option<&mut Recording>) -> Option<usize> {
+    let mut map = initial_map.clone();
+    // the elves move in circles if a state comes back, which is checked against the state saved at
+    // the last power of two of rounds (Brent's cycle detection); the order of the directions is part
+    // of the state, elves can move back to where they were 2 rounds before considering other directions
+    let mut saved = (map.clone(), 0);
+    let mut rounds = 0;
+    loop {
+        let new_map = round(&map, rounds);
+        rounds += 1;
+        if visualize::enabled() {
+            draw_map(&new_map, rounds);
+        }
+        if let Some(r) = &mut recording {
+            r.add(map_image(&new_map));
+        }
+        if new_map == map {
+            return Some(rounds);
         }
 
-        let res1 = count_empty_ground_tiles(&final_map);
-        println!("[Part 1] Result is {res1:?}");
+        map = new_map;
+        if rounds % 4 == saved.1 % 4 && map == saved.0 {
+            return None;
+        }
+        if rounds.is_power_of_two() {
+            saved = (map.clone(), rounds);
+        }
     }
+}
 
-    {
-        let mut map = initial_map.clone();
-        let mut i = 0;
-        loop {
-            let new_map = round(&map, i);
-            if map == new_map {
-                break; // no elves moved
-            }
+pub fn part2(initial_map: &Map) -> usize {
+    let mut recording = Recording::new("day23", &PALETTE, 4);
+    if let Some(r) = &mut recording {
+        r.add(map_image(initial_map));
+    }
+    let last_round = last_round(initial_map, recording.as_mut());
+    if let Some(r) = recording {
+        r.finish();
+    }
+    last_round.expect("the elves never stop moving")
+}
 
-            map = new_map;
-            i += 1;
-        }
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
 
-        let res2 = i + 1;
-        println!("[Part 2] Result is {res2:?}");
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example23");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 110);
+        assert_eq!(part2(&input), 20);
+    }
+
+    #[test]
+    fn test_round() {
+        let map = parse(".....\n..##.\n..#..\n.....\n..##.\n.....\n").unwrap();
+        let map1 = round(&map, 0);
+        assert_eq!(map1, parse("..##.\n.....\n..#..\n...#.\n..#..\n.....\n").unwrap());
+        let map2 = round(&map1, 1);
+        assert_eq!(map2, parse(".....\n..##.\n.#...\n....#\n.....\n..#..\n").unwrap());
+        let map3 = round(&map2, 2);
+        assert_eq!(map3, parse("..#..\n....#\n#....\n....#\n.....\n..#..\n").unwrap());
+        // no elf moves after the third round
+        assert_eq!(round(&map3, 3), map3);
+    }
+
+    #[test]
+    fn test_round_edge_cases() {
+        // an elf without neighbours stays put
+        let lone = parse("#").unwrap();
+        assert_eq!(round(&lone, 0), lone);
+
+        // the middle two both propose the empty tile between them, so neither of them moves
+        let column = parse("#\n#\n.\n#\n#\n").unwrap();
+        let expected = [vec2(0, -1), vec2(0, 1), vec2(0, 3), vec2(0, 5)].into_iter().collect::<Map>();
+        assert_eq!(round(&column, 0), expected);
+
+        // the order of the directions repeats every 4 rounds, and elves are never lost
+        let map = parse(EXAMPLE).unwrap();
+        for i in 0..4 {
+            assert_eq!(round(&map, i), round(&map, i + 4));
+            assert_eq!(round(&map, i).len(), map.len());
+        }
     }
 }
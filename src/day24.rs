@@ -1,182 +1,237 @@
-use std::collections::{HashSet, VecDeque};
-use std::ops;
-
-mod profiler;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Pos {
-    x: i64,
-    y: i64,
-}
-macro_rules! pos {
-    ($x:expr, $y:expr) => { Pos { x: $x, y: $y }};
-}
-
-impl ops::Add for Pos {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self {
-        pos!(self.x + rhs.x, self.y + rhs.y)
-    }
-}
-
-impl ops::Sub for Pos {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self {
-        pos!(self.x - rhs.x, self.y - rhs.y)
-    }
-}
-
-impl ops::Mul<i64> for Pos {
-    type Output = Self;
-
-    fn mul(self, rhs: i64) -> Self {
-        pos!(self.x * rhs, self.y * rhs)
-    }
-}
+use crate::geom::{vec2, Bounds, Dir, Vec2};
+use crate::grid::Grid;
+use crate::image::{Image, Recording, Rgb};
+use crate::parser::ParseError;
+use crate::search;
+use crate::visualize;
+use crate::{Answer, Solution};
 
 #[derive(Debug, Copy, Clone)]
-struct Blizzard {
-    pos: Pos,
-    dir: Pos,
+pub struct Blizzard {
+    pos: Vec2<i64>,
+    dir: Dir,
 }
 
 impl Blizzard {
-    fn get_position_at(&self, map: &Map, time: i64) -> Pos {
-        let p = self.pos + self.dir * time;
-        pos!(p.x.rem_euclid(map.width), p.y.rem_euclid(map.height))
+    fn get_position_at(&self, map: &Map, time: i64) -> Vec2<i64> {
+        let p = self.pos + self.dir.vector() * time;
+        vec2(p.x.rem_euclid(map.width), p.y.rem_euclid(map.height))
     }
 }
 
 #[derive(Debug, Clone)]
-struct Map {
+pub struct Map {
+    /// The valley as in the input, walls included, with the top-left corner inside the walls at (0, 0).
+    valley: Grid<u8>,
     width: i64,
     height: i64,
     blizzards: Vec<Blizzard>,
+    /// Directions of the blizzards at each position inside the walls at minute 0, as [`dir_bit`]s.
+    /// Blizzards stay in their row or column, so the ones at a position at any minute are found from
+    /// the one position each direction would come from.
+    blizzard_starts: Grid<u8>,
 }
 
-impl Map {
-    fn has_blizzard_at(&self, pos: Pos, time: i64) -> bool {
-        self.blizzards.iter().any(|b| b.get_position_at(self, time) == pos)
+fn dir_bit(dir: Dir) -> u8 {
+    match dir {
+        Dir::E => 1,
+        Dir::W => 2,
+        Dir::S => 4,
+        Dir::N => 8,
+        _ => unreachable!("diagonal blizzard {dir:?}"),
     }
+}
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        let mut buff = vec![vec![b'.'; self.width as usize + 2]; self.height as usize + 2];
-
-        for y in 0..buff.len() {
-            let row = &mut buff[y];
-            let last = row.len() - 1;
-            row[0] = b'#';
-            row[last] = b'#';
+impl Map {
+    fn has_blizzard_at(&self, pos: Vec2<i64>, time: i64) -> bool {
+        if !(0..self.width).contains(&pos.x) || !(0..self.height).contains(&pos.y) {
+            return false; // the entrance and the exit
         }
-        for x in 0..buff[0].len() {
-            let last = buff.len() - 1;
-            if x != 1 { buff[0][x] = b'#'; }
-            if x != self.width as usize { buff[last][x] = b'#'; }
+        let starts = |p: Vec2<i64>, dir: Dir| self.blizzard_starts[vec2(p.x.rem_euclid(self.width), p.y.rem_euclid(self.height))] & dir_bit(dir) != 0;
+        [Dir::E, Dir::W, Dir::S, Dir::N].into_iter().any(|dir| starts(pos - dir.vector() * time, dir))
+    }
+
+    /// The valley with the blizzards at `time` as in the puzzle, and the expedition at `pos` as `E`.
+    fn snapshot(&self, pos: Vec2<i64>, time: i64) -> Grid<u8> {
+        let mut buff = self.valley.clone();
+        for (p, c) in self.valley.cells() {
+            if *c != b'#' {
+                buff[p] = b'.';
+            }
         }
 
         for b in &self.blizzards {
-            let new_char = match buff[(b.pos.y + 1) as usize][(b.pos.x + 1) as usize] {
-                b'2'..=b'8' => buff[(b.pos.y + 1) as usize][(b.pos.x + 1) as usize] + 1,
+            let p = b.get_position_at(self, time);
+            let new_char = match buff[p] {
+                b'2'..=b'8' => buff[p] + 1,
                 b'>' | b'<' | b'v' | b'^' => b'2',
                 b'.' => match b.dir {
-                    pos!(1, 0) => b'>',
-                    pos!(-1, 0) => b'<',
-                    pos!(0, 1) => b'v',
-                    pos!(0, -1) => b'^',
+                    Dir::E => b'>',
+                    Dir::W => b'<',
+                    Dir::S => b'v',
+                    Dir::N => b'^',
                     _ => panic!(),
                 }
-                c => panic!("unexpected character '{}' at ({}, {})", std::char::from_u32(c as u32).unwrap(), b.pos.x, b.pos.y),
+                c => panic!("unexpected character '{}' at ({}, {})", std::char::from_u32(c as u32).unwrap(), p.x, p.y),
             };
 
-            buff[(b.pos.y + 1) as usize][(b.pos.x + 1) as usize] = new_char;
+            buff[p] = new_char;
         }
+        buff[pos] = b'E';
+        buff
+    }
 
-        for row in buff {
-            println!("{}", std::str::from_utf8(&row).unwrap());
-        }
+    /// Shows the blizzards at `time` and the expedition at `pos`.
+    fn draw(&self, pos: Vec2<i64>, time: i64) {
+        let buff = self.snapshot(pos, time);
+        let view = visualize::view(pos, Some(Bounds { min: buff.origin(), max: buff.max() }));
+        let body = visualize::render(view, |p| buff[p] as char);
+        visualize::frame(&format!("Day 24: minute {time}"), &body);
     }
 }
 
-fn pathfind_bfs(map: &Map, start: Pos, end: Pos, start_time: i64) -> Option<i64> {
-    type Step = (Pos, i64);
-
-    let width = map.width;
-    let height = map.height;
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::<Step>::new();
-    queue.push_back((start, start_time));
-    while let Some((curr, time)) = queue.pop_front() {
-        if curr == end {
-            return Some(time);
-        }
+/// Colours of the exported animation: the ground, walls, the expedition, then 1, 2, and 3 or more
+/// blizzards.
+const PALETTE: [Rgb; 6] = [
+    Rgb(230, 236, 244),
+    Rgb(56, 64, 80),
+    Rgb(232, 64, 32),
+    Rgb(150, 190, 232),
+    Rgb(80, 130, 208),
+    Rgb(24, 72, 168),
+];
+
+fn snapshot_image(map: &Map, pos: Vec2<i64>, time: i64) -> Image {
+    let buff = map.snapshot(pos, time);
+    Image::render(Bounds { min: buff.origin(), max: buff.max() }, |p| match buff[p] {
+        b'.' => 0,
+        b'#' => 1,
+        b'E' => 2,
+        b'>' | b'<' | b'^' | b'v' => 3,
+        b'2' => 4,
+        _ => 5,
+    })
+}
 
-        let next_time = time + 1;
-        let is_at_start = curr == start;
-        for pos_opt in [
-            if !is_at_start && curr.x > 0          { Some(pos!(curr.x - 1, curr.y)) } else { None }, // left
-            if !is_at_start && curr.y > 0          { Some(pos!(curr.x, curr.y - 1)) } else { None }, // up
-            if !is_at_start && curr.x < width - 1  { Some(pos!(curr.x + 1, curr.y)) } else { None }, // right
-            if !is_at_start && curr.y < height - 1 { Some(pos!(curr.x, curr.y + 1)) } else { None }, // down
-            Some(curr), // wait
-            // special cases for entrance and exit, since those are outside the map bounds
-            if curr == pos!(width - 1, height - 1) { Some(pos!(curr.x, curr.y + 1)) } else { None }, // down at bottom-right corner
-            if curr == pos!(0, 0)                  { Some(pos!(curr.x, curr.y - 1)) } else { None }, // up at top-left corner
-            if curr == pos!(0, -1)                 { Some(pos!(0, 0)) } else { None }, // down at entrance
-            if curr == pos!(width - 1, height)     { Some(pos!(width - 1, height - 1)) } else { None }, // up at exit
-        ] {
-            let Some(pos) = pos_opt else { continue; };
-            if !visited.contains(&(pos, next_time)) && !map.has_blizzard_at(pos, next_time) {
-                visited.insert((pos, next_time));
-                queue.push_back((pos, next_time));
-            }
+/// Earliest time `end` can be reached when leaving `start` at `start_time`, adding the frames of
+/// the trip to `recording`.
+fn earliest_arrival(map: &Map, start: Vec2<i64>, end: Vec2<i64>, start_time: i64, recording: &mut Option<Recording>) -> Option<i64> {
+    // move in any direction that isn't a wall (the entrance and exit are the only gaps in the
+    // walls), or wait, as long as no blizzard is there at the next minute
+    let successors = |&(pos, time): &(Vec2<i64>, i64)| {
+        let moves = map.valley.neighbours4(pos).filter(|&p| map.valley[p] != b'#');
+        moves.chain([pos])
+            .filter(move |&p| !map.has_blizzard_at(p, time + 1))
+            .map(move |p| (p, time + 1))
+    };
+    // the path is only needed to show the trip
+    if !visualize::enabled() && recording.is_none() {
+        let search = search::bfs_cost((start, start_time), successors, |&(pos, _)| pos == end);
+        crate::debug!("{start} -> {end}: {} states visited", search.visited);
+        return search.cost.map(|cost| start_time + cost as i64);
+    }
+    let search = search::bfs((start, start_time), successors, |&(pos, _)| pos == end);
+    crate::debug!("{start} -> {end}: {} states visited", search.visited);
+    let path = search.path?;
+    if visualize::enabled() {
+        for &(pos, time) in &path.states {
+            map.draw(pos, time);
         }
     }
+    if let Some(r) = recording {
+        for &(pos, time) in &path.states {
+            r.add(snapshot_image(map, pos, time));
+        }
+    }
+    Some(path.goal().1)
+}
+
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let width = input.lines().next().map_or(0, str::len);
+    if let Some(row) = input.lines().find(|row| row.len() < 3 || row.len() != width) {
+        return Err(ParseError::at(input, row, "a row as wide as the valley, walls included"));
+    }
+    let valley = Grid::parse(input, input, "'#', '.', '>', '<', '^' or 'v'", |c| {
+        matches!(c, b'#' | b'.' | b'>' | b'<' | b'^' | b'v').then_some(c)
+    })?.with_origin(vec2(-1, -1));
+    if valley.height() < 3 {
+        return Err(ParseError::after(input, input, "a valley at least one row tall"));
+    }
 
-    None
+    let blizzards = valley.cells()
+        .filter_map(|(pos, c)| {
+            let dir = match c {
+                b'>' => Dir::E,
+                b'<' => Dir::W,
+                b'^' => Dir::N,
+                b'v' => Dir::S,
+                _ => return None,
+            };
+            Some(Blizzard { pos, dir })
+        })
+        .collect::<Vec<_>>();
+    let (width, height) = (valley.width() as i64 - 2, valley.height() as i64 - 2);
+    let mut blizzard_starts = Grid::new(width as usize, height as usize, 0);
+    for b in &blizzards {
+        blizzard_starts[b.pos] |= dir_bit(b.dir);
+    }
+    Ok(Map { valley, width, height, blizzards, blizzard_starts })
 }
 
-fn main() {
-    let _p = profiler::profile();
-
-    const INPUT: &str = include_str!("data/input24");
-    let initial_map = INPUT
-        .lines().enumerate()
-        .fold(Map { width: 0, height: -2, blizzards: Vec::new() },
-        |mut map, (y, row)| {
-            row.as_bytes().iter().enumerate().for_each(|(x, &c)| {
-                let pos = pos!(x as i64 - 1, y as i64 - 1);
-                if let Some(blizzard) = match c {
-                    b'>' => Some(Blizzard { pos, dir: pos!(1, 0) }),
-                    b'<' => Some(Blizzard { pos, dir: pos!(-1, 0) }),
-                    b'^' => Some(Blizzard { pos, dir: pos!(0, -1) }),
-                    b'v' => Some(Blizzard { pos, dir: pos!(0, 1) }),
-                    _ => None
-                } {
-                    map.blizzards.push(blizzard);
-                }
-            });
-            if map.width == 0 { map.width = (row.as_bytes().len() - 2) as i64; }
-            map.height += 1;
-            map
-        });
-
-    let start = pos!(0, -1);
-    let end = pos!(initial_map.width - 1, initial_map.height);
-
-    let res1 = pathfind_bfs(&initial_map, start, end, 0).unwrap();
-    println!("[Part 1] Result is {res1:?}");
-
-    let second_trip = pathfind_bfs(&initial_map, end, start, res1).unwrap();
-    let res2 = pathfind_bfs(&initial_map, start, end, second_trip).unwrap();
-    println!("[Part 2] Result is {res2:?}");
-
-    // let mut map = initial_map.clone();
-    // for i in 0..res1.len() {
-    //     println!("==== Minute {} ====", map.time);
-    //     map.print();
-    //     map = map.next();
-    // }
+fn entrance(_map: &Map) -> Vec2<i64> {
+    vec2(0, -1)
+}
+
+fn exit(map: &Map) -> Vec2<i64> {
+    vec2(map.width - 1, map.height)
+}
+
+pub fn part1(map: &Map) -> i64 {
+    earliest_arrival(map, entrance(map), exit(map), 0, &mut None).unwrap()
+}
+
+pub fn part2(map: &Map) -> i64 {
+    let (start, end) = (entrance(map), exit(map));
+    // the recording shows the three trips
+    let mut recording = Recording::new("day24", &PALETTE, 4);
+    let first_trip = earliest_arrival(map, start, end, 0, &mut recording).unwrap();
+    let second_trip = earliest_arrival(map, end, start, first_trip, &mut recording).unwrap();
+    let third_trip = earliest_arrival(map, start, end, second_trip, &mut recording).unwrap();
+    if let Some(r) = recording {
+        r.finish();
+    }
+    third_trip
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example24");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 18);
+        assert_eq!(part2(&input), 54);
+    }
 }
@@ -1,13 +1,15 @@
-mod profiler;
+use crate::checked;
+use crate::parser::ParseError;
+use crate::{Answer, Solution};
 
-fn snafu_digit_value(c: u8) -> i64 {
+fn snafu_digit_value(c: u8) -> Option<i64> {
     match c {
-        b'2' => 2,
-        b'1' => 1,
-        b'0' => 0,
-        b'-' => -1,
-        b'=' => -2,
-        _ => panic!("unexpected character")
+        b'2' => Some(2),
+        b'1' => Some(1),
+        b'0' => Some(0),
+        b'-' => Some(-1),
+        b'=' => Some(-2),
+        _ => None
     }
 }
 
@@ -22,38 +24,105 @@ fn value_to_snafu_digit(n: i64) -> u8 {
     }
 }
 
-fn parse_snafu(s: &str) -> i64 {
-    let s = s.as_bytes();
+fn parse_snafu(input: &str, s: &str) -> Result<i64, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::at(input, s, "a SNAFU number"));
+    }
     let mut res = 0i64;
-    let mut multiple = 1;
-    for &c in s.iter().rev() {
-        res += snafu_digit_value(c) * multiple;
-        multiple *= 5;
+    for (i, c) in s.bytes().enumerate() {
+        let digit = snafu_digit_value(c).ok_or_else(|| ParseError::at(input, &s[i..], "'2', '1', '0', '-' or '='"))?;
+        res = res.checked_mul(5).and_then(|n| n.checked_add(digit))
+            .ok_or_else(|| ParseError::at(input, s, "a SNAFU number that fits in 64 bits"))?;
     }
-    res
+    Ok(res)
 }
 
 fn to_snafu(n: i64) -> String {
+    if n == 0 {
+        return "0".into();
+    }
     let mut s = Vec::with_capacity(8);
     let mut remaining = n;
     while remaining != 0 {
         remaining += 2;
-        let digit = remaining % 5 - 2;
+        let digit = remaining.rem_euclid(5) - 2;
         s.push(value_to_snafu_digit(digit));
-        remaining = remaining / 5;
+        remaining = remaining.div_euclid(5);
     }
     s.reverse();
     std::str::from_utf8(&s).unwrap().into()
 }
 
-fn main() {
-    let _p = profiler::profile();
+pub fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    input.lines()
+        .map(|l| parse_snafu(input, l))
+        .collect()
+}
+
+pub fn part1(numbers: &[i64]) -> String {
+    to_snafu(checked::sum(Day25::DAY, numbers.iter().copied()))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(_input: &Self::Input) -> Answer {
+        Answer::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    const INPUT: &str = include_str!("data/input25");
+    const EXAMPLE: &str = include_str!("data/example25");
 
-    let res1 = INPUT.lines()
-        .map(parse_snafu)
-        .sum::<i64>();
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), "2=-1=0");
+    }
+
+    #[test]
+    fn test_snafu() {
+        let pairs = [
+            (1, "1"), (2, "2"), (3, "1="), (4, "1-"), (5, "10"), (6, "11"), (7, "12"), (8, "2="), (9, "2-"),
+            (10, "20"), (15, "1=0"), (20, "1-0"), (2022, "1=11-2"), (12345, "1-0---0"), (314159265, "1121-1110-1=0"),
+        ];
+        for (n, s) in pairs {
+            assert_eq!(to_snafu(n), s);
+            assert_eq!(parse_snafu(s, s), Ok(n));
+        }
+        assert!(parse_snafu("1=3", "1=3").is_err());
+    }
 
-    println!("[Part 1] Result is {} ({})", to_snafu(res1), res1);
+    #[test]
+    fn test_snafu_roundtrip() {
+        let around_powers = (0..27).map(|k| 5i64.pow(k)).flat_map(|p| [p - 3, p - 2, p - 1, p, p + 1, p + 2, p + 3]);
+        for n in (-1000..=1000).chain(around_powers).chain([i64::MAX / 2, -i64::MAX / 2]) {
+            let s = to_snafu(n);
+            assert_eq!(parse_snafu(&s, &s), Ok(n), "{n} as {s}");
+        }
+        assert_eq!(to_snafu(0), "0");
+        assert_eq!(to_snafu(-3), "-2");
+        assert_eq!(parse_snafu("0", "0"), Ok(0));
+        assert!(parse_snafu("", "").is_err());
+
+        // one more digit than the largest numbers above, past i64::MAX
+        let too_long = format!("1{}", to_snafu(i64::MAX / 2));
+        let err = parse_snafu(&too_long, &too_long).unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (1, "a SNAFU number that fits in 64 bits"));
+        assert!(parse_snafu("2222222222222222222222222222", "2222222222222222222222222222").is_err());
+    }
 }
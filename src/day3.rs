@@ -1,5 +1,5 @@
-
-mod profiler;
+use crate::parser::ParseError;
+use crate::{Answer, Solution};
 
 fn priority(item: u8) -> u64 {
     match item {
@@ -9,14 +9,19 @@ fn priority(item: u8) -> u64 {
     }
 }
 
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input3");
-    let res1 = input
+pub fn parse(input: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    input
         .lines()
+        .map(|l| match l.bytes().position(|c| !c.is_ascii_alphabetic()) {
+            Some(i) => Err(ParseError::at(input, &l[i..], "an item (a-z or A-Z)")),
+            None => Ok(l.as_bytes().to_vec()),
+        })
+        .collect()
+}
+
+pub fn part1(rucksacks: &[Vec<u8>]) -> u64 {
+    rucksacks.iter()
         .map(|l| l.split_at(l.len() / 2))
-        .map(|(a, b)| (a.as_bytes(), b.as_bytes()))
         .map(|(a, b)| {
             for ca in a {
                 for cb in b {
@@ -25,14 +30,11 @@ fn main() {
             }
             unreachable!()
         })
-        .sum::<u64>();
-    println!("[Part 1] Result is {res1}");
-
+        .sum::<u64>()
+}
 
-    let res2 = input
-        .lines()
-        .map(|l| l.as_bytes())
-        .collect::<Vec<&[u8]>>()
+pub fn part2(rucksacks: &[Vec<u8>]) -> u64 {
+    rucksacks
         .chunks(3)
         .map(|group| {
             let a = group[0].iter().map(|i| priority(*i) as usize);
@@ -45,6 +47,38 @@ fn main() {
 
             frequency.iter().position(|freq| *freq == 3).unwrap() as u64
         })
-        .sum::<u64>();
-    println!("[Part 2] Result is {res2}");
+        .sum::<u64>()
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    type Input = Vec<Vec<u8>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example3");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 157);
+        assert_eq!(part2(&input), 70);
+    }
 }
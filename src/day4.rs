@@ -1,33 +1,71 @@
-mod profiler;
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
-fn main() {
-    let _p = profiler::profile();
+/// Inclusive section range `(min, max)`.
+pub type Range = (i64, i64);
 
-    let input = include_str!("data/input4");
-    let input_pairs = input
+pub fn parse(input: &str) -> Result<Vec<(Range, Range)>, ParseError> {
+    let parse_range = |s: &str| -> Result<Range, ParseError> {
+        let (min, max) = parser::split_once(input, s, "-")?;
+        Ok((parser::number(input, min)?, parser::number(input, max)?))
+    };
+
+    input
         .lines()
-        .map(|l| l.split_once(',').unwrap())
-        .map(|(a, b)|
-            (a.split_once('-').unwrap(),
-             b.split_once('-').unwrap()))
-        .map(|((a_min, a_max), (b_min, b_max))|
-            ((a_min.parse::<i64>().unwrap(), a_max.parse::<i64>().unwrap()),
-             (b_min.parse::<i64>().unwrap(), b_max.parse::<i64>().unwrap())))
-        .collect::<Vec<_>>();
-
-    let res1 = input_pairs.iter()
+        .map(|l| {
+            let (a, b) = parser::split_once(input, l, ",")?;
+            Ok((parse_range(a)?, parse_range(b)?))
+        })
+        .collect()
+}
+
+pub fn part1(pairs: &[(Range, Range)]) -> usize {
+    pairs.iter()
         .filter(|((a_min, a_max), (b_min, b_max))|
             (*a_min >= *b_min && *a_max <= *b_max) ||
             (*b_min >= *a_min && *b_max <= *a_max))
-        .count();
-    println!("[Part 1] Result is {res1}");
+        .count()
+}
 
-    let res2 = input_pairs.iter()
+pub fn part2(pairs: &[(Range, Range)]) -> usize {
+    pairs.iter()
         .filter(|((a_min, a_max), (b_min, b_max))|
             (*a_min >= *b_min && *a_min <= *b_max) ||
             (*a_max >= *b_min && *a_max <= *b_max) ||
             (*b_min >= *a_min && *b_min <= *a_max) ||
             (*b_max >= *a_min && *b_max <= *a_max))
-        .count();
-    println!("[Part 2] Result is {res2}");
+        .count()
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    type Input = Vec<(Range, Range)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example4");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 2);
+        assert_eq!(part2(&input), 4);
+    }
 }
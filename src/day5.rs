@@ -1,53 +1,115 @@
-mod profiler;
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
-fn main() {
-    let _p = profiler::profile();
+#[derive(Debug, Copy, Clone)]
+pub struct Move {
+    num: usize,
+    from: usize,
+    to: usize,
+}
 
-    let input = include_str!("data/input5");
+#[derive(Debug, Clone)]
+pub struct Procedure {
+    stacks: Vec<Vec<char>>,
+    moves: Vec<Move>,
+}
 
+pub fn parse(input: &str) -> Result<Procedure, ParseError> {
     let input_stacks = input.lines().take_while(|l| !l.is_empty()).collect::<Vec<_>>();
-    let num_stacks = input_stacks.last().unwrap()
-                                        .rsplit_once(' ').unwrap().1
-                                        .parse::<usize>().unwrap();
+    let Some(&stack_numbers) = input_stacks.last() else {
+        return Err(ParseError::at(input, input, "a drawing of the stacks"));
+    };
+    let num_stacks = parser::number::<usize>(input, stack_numbers.trim_end().rsplit_once(' ').map_or(stack_numbers, |s| s.1))?;
 
-    let mut stacks1 = vec![Vec::<char>::new(); num_stacks];
+    let mut stacks = vec![Vec::<char>::new(); num_stacks];
 
     for input_row in input_stacks.iter().rev().skip(1) {
-        for i in 0..num_stacks {
+        for (i, stack) in stacks.iter_mut().enumerate() {
             let offset = i * 4 + 1;
             let crate_id = input_row.chars().nth(offset).unwrap_or(' ');
             if crate_id != ' ' {
-                stacks1[i].push(crate_id);
+                stack.push(crate_id);
             }
         }
     }
 
-    let mut stacks2 = stacks1.clone(); // for part 2
+    let moves = input.lines().skip_while(|l| !l.is_empty()).skip(1)
+        .map(|cmd| {
+            let cmd_rest = parser::strip_prefix(input, cmd, "move ")?;
+            let (num, cmd_rest) = parser::split_once(input, cmd_rest, " from ")?;
+            let (from, to) = parser::split_once(input, cmd_rest, " to ")?;
+            let stack_index = |s: &str| match parser::number::<usize>(input, s)? {
+                n @ 1.. if n <= num_stacks => Ok(n - 1),
+                _ => Err(ParseError::at(input, s, format!("a stack number between 1 and {num_stacks}"))),
+            };
+            Ok(Move {
+                num: parser::number(input, num)?,
+                from: stack_index(from)?,
+                to: stack_index(to)?,
+            })
+        })
+        .collect::<Result<_, _>>()?;
 
-    let input_commands = input.lines().skip_while(|l| !l.is_empty()).skip(1).collect::<Vec<_>>();
-    for cmd in input_commands {
-        let parts = cmd.split(' ').collect::<Vec<_>>();
-        let num = parts[1].parse::<usize>().unwrap();
-        let from = parts[3].parse::<usize>().unwrap() - 1;
-        let to = parts[5].parse::<usize>().unwrap() - 1;
+    Ok(Procedure { stacks, moves })
+}
+
+fn top_crates(stacks: &[Vec<char>]) -> String {
+    stacks.iter().map(|s| *s.last().unwrap()).collect::<String>()
+}
 
-        // part 1
+pub fn part1(procedure: &Procedure) -> String {
+    let mut stacks = procedure.stacks.clone();
+    for &Move { num, from, to } in &procedure.moves {
         for _ in 0..num {
-            let crate_id = stacks1[from].pop().unwrap();
-            stacks1[to].push(crate_id);
+            let crate_id = stacks[from].pop().unwrap();
+            stacks[to].push(crate_id);
         }
+    }
+    top_crates(&stacks)
+}
 
-        // part 2
-        let num_remaining = stacks2[from].len() - num;
+pub fn part2(procedure: &Procedure) -> String {
+    let mut stacks = procedure.stacks.clone();
+    for &Move { num, from, to } in &procedure.moves {
+        let num_remaining = stacks[from].len() - num;
         for i in 0..num {
-            let crate_id = stacks2[from][num_remaining + i];
-            stacks2[to].push(crate_id);
+            let crate_id = stacks[from][num_remaining + i];
+            stacks[to].push(crate_id);
         }
-        stacks2[from].truncate(num_remaining);
+        stacks[from].truncate(num_remaining);
+    }
+    top_crates(&stacks)
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    type Input = Procedure;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let res1 = stacks1.iter().map(|s| *s.last().unwrap()).collect::<String>();
-    let res2 = stacks2.iter().map(|s| *s.last().unwrap()).collect::<String>();
-    println!("[Part 1] Result is {res1}");
-    println!("[Part 2] Result is {res2}");
+    const EXAMPLE: &str = include_str!("data/example5");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), "CMZ");
+        assert_eq!(part2(&input), "MCD");
+    }
 }
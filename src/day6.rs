@@ -1,17 +1,60 @@
-mod profiler;
+use crate::parser::ParseError;
+use crate::{Answer, Solution};
 
-fn main() {
-    let _p = profiler::profile();
+pub fn parse(input: &str) -> Result<Vec<u8>, ParseError> {
+    let datastream = input.trim();
+    match datastream.bytes().position(|c| !c.is_ascii_lowercase()) {
+        Some(i) => Err(ParseError::at(input, &datastream[i..], "a character (a-z)")),
+        None => Ok(datastream.as_bytes().to_vec()),
+    }
+}
+
+fn find_marker(datastream: &[u8], marker_length: usize) -> usize {
+    let (start_index, _) = datastream
+        .windows(marker_length)
+        .enumerate()
+        .find(|(_, w)| w.iter().all(|&n| w.iter().filter(|&&m| m == n).count() == 1))
+        .unwrap();
+    start_index + marker_length
+}
+
+pub fn part1(datastream: &[u8]) -> usize {
+    find_marker(datastream, 4)
+}
+
+pub fn part2(datastream: &[u8]) -> usize {
+    find_marker(datastream, 14)
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    type Input = Vec<u8>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let input = include_str!("data/input6");
+    const EXAMPLE: &str = include_str!("data/example6");
 
-    for (part, &marker_length) in [4usize, 14].iter().enumerate() {
-        let (start_index, _) = input.as_bytes()
-            .windows(marker_length)
-            .enumerate()
-            .find(|(_, w)| w.iter().all(|&n| w.iter().filter(|&&m| m == n).count() == 1))
-            .unwrap();
-        let res = start_index + marker_length;
-        println!("[Part {}] Result is {res}", part + 1);
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 7);
+        assert_eq!(part2(&input), 19);
     }
 }
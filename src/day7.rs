@@ -1,15 +1,13 @@
-extern crate core;
-
 use std::collections::HashMap;
 use std::ops::Add;
-mod profiler;
-
-fn main() {
-    let _p = profiler::profile();
+use crate::parser::{self, ParseError};
+use crate::{Answer, Solution};
 
-    let input = include_str!("data/input7");
+/// Total size of each directory, keyed by its absolute path (the root is `/`).
+pub type DirSizes = HashMap<String, usize>;
 
-    let mut dir_sizes: HashMap<String, usize> = HashMap::new();
+pub fn parse(input: &str) -> Result<DirSizes, ParseError> {
+    let mut dir_sizes: DirSizes = HashMap::new();
     let mut wd: String = "/".into();
 
     let input_lines = input.lines().collect::<Vec<_>>();
@@ -18,20 +16,24 @@ fn main() {
         let line = input_lines[i];
         let mut parts = line.split(' ');
         match (parts.next(), parts.next()) {
-            (Some("$"), Some("cd")) => wd = match parts.next().unwrap() {
-                "/" => "/".into(),
-                ".." => wd.rsplit_once('/').unwrap().0.into(),
-                dir_name => wd.add("/").add(dir_name),
+            (Some("$"), Some("cd")) => wd = match parts.next() {
+                Some("/") => "/".into(),
+                Some("..") => match wd.rsplit_once('/') {
+                    Some((parent, _)) => parent.into(),
+                    None => return Err(ParseError::at(input, line, "a directory to go up from")),
+                },
+                Some(dir_name) => wd.add("/").add(dir_name),
+                None => return Err(ParseError::after(input, line, "a directory name")),
             },
             (Some("$"), Some("ls")) => {
-                while (i + 1) < input_lines.len() && input_lines[i + 1].chars().next() != Some('$') {
+                while (i + 1) < input_lines.len() && !input_lines[i + 1].starts_with('$') {
                     i += 1;
-                    let (size_or_dir, _) = input_lines[i].split_once(' ').unwrap();
+                    let (size_or_dir, _) = parser::split_once(input, input_lines[i], " ")?;
                     if size_or_dir == "dir" {
                         // empty
                     } else {
                         // add file to directory
-                        let file_size = size_or_dir.parse::<usize>().unwrap();
+                        let file_size = parser::number::<usize>(input, size_or_dir)?;
                         if let Some(size) = dir_sizes.get(&wd) {
                             dir_sizes.insert(wd.clone(), size + file_size);
                         } else {
@@ -51,22 +53,59 @@ fn main() {
                     }
                 }
             }
-            invalid_input => panic!("invalid input: {:?}", invalid_input)
+            _ => return Err(ParseError::at(input, line, "'$ cd' or '$ ls'")),
         }
         i += 1;
     }
 
+    Ok(dir_sizes)
+}
+
+pub fn part1(dir_sizes: &DirSizes) -> usize {
     const LIMIT_SIZE: usize = 100_000;
-    let res1 = dir_sizes.values().filter(|&&s| s <= LIMIT_SIZE).sum::<usize>();
-    println!("[Part 1] Result is {res1}");
+    dir_sizes.values().filter(|&&s| s <= LIMIT_SIZE).sum::<usize>()
+}
 
+pub fn part2(dir_sizes: &DirSizes) -> usize {
     const DISK_SIZE: usize = 70_000_000;
     const REQUIRED_SPACE: usize = 30_000_000;
     let used_space = *dir_sizes.get("/").unwrap();
-    println!("[Part 2] {used_space} / {DISK_SIZE} (unused {})", DISK_SIZE - used_space);
-    let mut sizes = dir_sizes.into_values().collect::<Vec<usize>>();
+    crate::debug!("[Part 2] {used_space} / {DISK_SIZE} (unused {})", DISK_SIZE - used_space);
+    let mut sizes = dir_sizes.values().copied().collect::<Vec<usize>>();
     sizes.sort_unstable();
-    println!("[Part 2] {:?}", sizes);
-    let res2 = sizes.into_iter().find(|&s| (DISK_SIZE - used_space + s) >= REQUIRED_SPACE).unwrap();
-    println!("[Part 2] Result is {res2}");
+    crate::trace!("[Part 2] {:?}", sizes);
+    sizes.into_iter().find(|&s| (DISK_SIZE - used_space + s) >= REQUIRED_SPACE).unwrap()
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    type Input = DirSizes;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example7");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 95437);
+        assert_eq!(part2(&input), 24933642);
+    }
 }
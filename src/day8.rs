@@ -1,45 +1,75 @@
-mod profiler;
-
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input8");
-    let grid = input
-        .lines()
-        .map(|l| l.as_bytes())
-        .collect::<Vec<_>>();
-    let res1 =
-        grid.iter().enumerate().map(|(y, &row)| {
-            row.iter().enumerate().filter(|&(x, &tree)| {
-                let shorter_than_tree = |other: u8| other < tree;
-                row[..x].iter().map(|c| *c).all(&shorter_than_tree) ||      // left
-                row[x+1..].iter().map(|c| *c).all(&shorter_than_tree) ||    // right
-                grid[..y].iter().map(|r| r[x]).all(&shorter_than_tree) || // up
-                grid[y+1..].iter().map(|r| r[x]).all(&shorter_than_tree)  // down
-            }).count()
-        }).sum::<usize>();
-    println!("[Part 1] Result is {res1}");
-
-    let res2 =
-        grid.iter().enumerate().map(|(y, &row)| {
-            row.iter().enumerate().map(|(x, &tree)| {
-                fn distance_until_blocked<I>(it: I, tree: u8) -> Option<usize> where I: Iterator<Item = u8> {
-                    it.enumerate()
-                      .find_map(|(i, other)| if other >= tree { Some(i + 1) } else { None })
-                }
-
-                let dist_to_edge_left = x;
-                let dist_to_edge_right = row.len() - x - 1;
-                let dist_to_edge_up = y;
-                let dist_to_edge_down = grid.len() - y - 1;
-
-                let left = distance_until_blocked(row[..x].iter().rev().map(|c| *c), tree).unwrap_or(dist_to_edge_left);
-                let right = distance_until_blocked(row[x+1..].iter().map(|c| *c), tree).unwrap_or(dist_to_edge_right);
-                let up = distance_until_blocked(grid[..y].iter().rev().map(|r| r[x]), tree).unwrap_or(dist_to_edge_up);
-                let down = distance_until_blocked(grid[y+1..].iter().map(|r| r[x]), tree).unwrap_or(dist_to_edge_down);
-
-                left * right * up * down
-            }).max().unwrap()
-        }).max().unwrap();
-    println!("[Part 2] Result is {res2}");
+use crate::grid::Grid;
+use crate::parser::ParseError;
+use crate::{Answer, Solution};
+
+pub fn parse(input: &str) -> Result<Grid<u8>, ParseError> {
+    Grid::parse(input, input, "a tree height (0-9)", |c| c.is_ascii_digit().then_some(c))
+}
+
+pub fn part1(grid: &Grid<u8>) -> usize {
+    grid.rows().enumerate().map(|(y, row)| {
+        row.iter().enumerate().filter(|&(x, &tree)| {
+            let shorter_than_tree = |other: &u8| *other < tree;
+            row[..x].iter().all(shorter_than_tree) ||                      // left
+            row[x+1..].iter().all(shorter_than_tree) ||                    // right
+            grid.rows().take(y).map(|r| &r[x]).all(shorter_than_tree) ||   // up
+            grid.rows().skip(y + 1).map(|r| &r[x]).all(shorter_than_tree)  // down
+        }).count()
+    }).sum::<usize>()
+}
+
+pub fn part2(grid: &Grid<u8>) -> usize {
+    grid.rows().enumerate().map(|(y, row)| {
+        row.iter().enumerate().map(|(x, &tree)| {
+            fn distance_until_blocked<'a, I>(it: I, tree: u8) -> Option<usize> where I: Iterator<Item = &'a u8> {
+                it.enumerate()
+                  .find_map(|(i, &other)| if other >= tree { Some(i + 1) } else { None })
+            }
+
+            let dist_to_edge_left = x;
+            let dist_to_edge_right = row.len() - x - 1;
+            let dist_to_edge_up = y;
+            let dist_to_edge_down = grid.height() - y - 1;
+
+            let left = distance_until_blocked(row[..x].iter().rev(), tree).unwrap_or(dist_to_edge_left);
+            let right = distance_until_blocked(row[x+1..].iter(), tree).unwrap_or(dist_to_edge_right);
+            let up = distance_until_blocked(grid.rows().take(y).rev().map(|r| &r[x]), tree).unwrap_or(dist_to_edge_up);
+            let down = distance_until_blocked(grid.rows().skip(y + 1).map(|r| &r[x]), tree).unwrap_or(dist_to_edge_down);
+
+            left * right * up * down
+        }).max().unwrap()
+    }).max().unwrap()
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    type Input = Grid<u8>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example8");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 21);
+        assert_eq!(part2(&input), 8);
+    }
 }
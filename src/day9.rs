@@ -1,38 +1,25 @@
 use std::collections::HashSet;
-use std::iter::repeat;
+use std::iter::repeat_n;
 
-mod profiler;
+use crate::geom::{vec2, Dir, Vec2};
+use crate::parser::{self, ParseError};
+use crate::visualize;
+use crate::{Answer, Solution};
 
-type Pos = (i32, i32);
 struct Bridge<const NUM_KNOTS: usize> {
-    knots: [Pos; NUM_KNOTS],
-    tail_visited: HashSet<Pos>,
+    knots: [Vec2<i32>; NUM_KNOTS],
+    tail_visited: HashSet<Vec2<i32>>,
 }
 
 impl<const NUM_KNOTS: usize> Bridge<NUM_KNOTS> {
     fn new() -> Self {
         let mut visited = HashSet::new();
-        visited.insert((0, 0));
-        Self { knots: [(0, 0); NUM_KNOTS], tail_visited: visited }
+        visited.insert(vec2(0, 0));
+        Self { knots: [vec2(0, 0); NUM_KNOTS], tail_visited: visited }
     }
 
-    fn left(&mut self) {
-        self.knots[0] = (self.knots[0].0 - 1, self.knots[0].1);
-        self.tail_follow(1);
-    }
-
-    fn right(&mut self) {
-        self.knots[0] = (self.knots[0].0 + 1, self.knots[0].1);
-        self.tail_follow(1);
-    }
-
-    fn up(&mut self) {
-        self.knots[0] = (self.knots[0].0, self.knots[0].1 - 1);
-        self.tail_follow(1);
-    }
-
-    fn down(&mut self) {
-        self.knots[0] = (self.knots[0].0, self.knots[0].1 + 1);
+    fn move_head(&mut self, dir: Dir) {
+        self.knots[0] += dir.vector();
         self.tail_follow(1);
     }
 
@@ -42,9 +29,8 @@ impl<const NUM_KNOTS: usize> Bridge<NUM_KNOTS> {
         let head = self.knots[tail_knot_index - 1];
         let tail = self.knots[tail_knot_index];
 
-        let dist_horizontal = head.0 - tail.0;
-        let dist_vertical = head.1 - tail.1;
-        self.knots[tail_knot_index] = (tail.0 + dist_horizontal.signum(), tail.1 + dist_vertical.signum());
+        let dist = head - tail;
+        self.knots[tail_knot_index] = tail + vec2(dist.x.signum(), dist.y.signum());
         if tail_knot_index == NUM_KNOTS - 1 {
             self.tail_visited.insert(self.knots[tail_knot_index]);
         } else {
@@ -52,32 +38,103 @@ impl<const NUM_KNOTS: usize> Bridge<NUM_KNOTS> {
         }
     }
 
+    /// Shows the knots around the head, over the positions visited by the tail.
+    fn draw(&self, step: usize) {
+        let head = self.knots[0];
+        let view = visualize::view(vec2(head.x as i64, head.y as i64), None);
+        let body = visualize::render(view, |p| {
+            let p = vec2(p.x as i32, p.y as i32);
+            match self.knots.iter().position(|&k| k == p) {
+                Some(0) => 'H',
+                Some(_) if NUM_KNOTS == 2 => 'T',
+                Some(i) => char::from_digit(i as u32, 10).unwrap(),
+                None if p == vec2(0, 0) => 's',
+                None if self.tail_visited.contains(&p) => '#',
+                None => '.',
+            }
+        });
+        visualize::frame(&format!("Day 9: {NUM_KNOTS} knots, step {step}, {} positions visited", self.tail_visited.len()), &body);
+    }
+
     fn tail_is_touching(&self, tail_knot_index: usize) -> bool {
         let head = self.knots[tail_knot_index - 1];
         let tail = self.knots[tail_knot_index];
-        (head.0 - tail.0).abs() <= 1 && (head.1 - tail.1).abs() <= 1
+        head.chebyshev(tail) <= 1
     }
 }
 
-fn main() {
-    let _p = profiler::profile();
-
-    let input = include_str!("data/input9");
-
-    let mut bridge1: Bridge<2> = Bridge::new();
-    let mut bridge2: Bridge<10> = Bridge::new();
+/// Head motions as `(direction, steps)`, direction being one of `L`, `R`, `U` or `D`.
+pub fn parse(input: &str) -> Result<Vec<(char, usize)>, ParseError> {
     input
         .lines()
-        .map(|l| l.split_once(' ').unwrap())
-        .map(|(dir, count)| (dir.chars().next().unwrap(), count.parse::<usize>().unwrap()))
-        .flat_map(|(dir, count)| repeat(dir).take(count))
-        .for_each(|dir| match dir {
-            'L' => { bridge1.left(); bridge2.left(); },
-            'R' => { bridge1.right(); bridge2.right(); },
-            'U' => { bridge1.up(); bridge2.up(); },
-            'D' => { bridge1.down(); bridge2.down(); },
-            _ => panic!("unknown move"),
+        .map(|l| {
+            let (dir, count) = parser::split_once(input, l, " ")?;
+            let dir = match dir {
+                "L" | "R" | "U" | "D" => dir.chars().next().unwrap(),
+                _ => return Err(ParseError::at(input, dir, "'L', 'R', 'U' or 'D'")),
+            };
+            Ok((dir, parser::number(input, count)?))
+        })
+        .collect()
+}
+
+fn simulate<const NUM_KNOTS: usize>(motions: &[(char, usize)]) -> usize {
+    let mut bridge: Bridge<NUM_KNOTS> = Bridge::new();
+    motions.iter()
+        .flat_map(|&(dir, count)| repeat_n(dir, count))
+        .enumerate()
+        .for_each(|(step, dir)| {
+            match dir {
+                'L' => bridge.move_head(Dir::W),
+                'R' => bridge.move_head(Dir::E),
+                'U' => bridge.move_head(Dir::N),
+                'D' => bridge.move_head(Dir::S),
+                _ => panic!("unknown move"),
+            }
+            if visualize::enabled() {
+                bridge.draw(step + 1);
+            }
         });
-    println!("[Part 1] Result is {}", bridge1.tail_visited.len());
-    println!("[Part 2] Result is {}", bridge2.tail_visited.len());
+    bridge.tail_visited.len()
+}
+
+pub fn part1(motions: &[(char, usize)]) -> usize {
+    simulate::<2>(motions)
+}
+
+pub fn part2(motions: &[(char, usize)]) -> usize {
+    simulate::<10>(motions)
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    type Input = Vec<(char, usize)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("data/example9");
+
+    #[test]
+    fn test_example() {
+        let input = parse(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 13);
+        assert_eq!(part2(&input), 1);
+    }
 }
@@ -0,0 +1,788 @@
+//! Random puzzle inputs, to test and benchmark the solutions on more than the one input of each day.
+//!
+//! Every day has a [`Generator`] producing inputs of a tunable size that the solutions can solve,
+//! such as a single path from `root` to `humn` in day 21 or a valley the expedition can cross in day
+//! 24. The same seed always gives the same input.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use crate::geom::{vec2, vec3, Vec2};
+use crate::{day23, search};
+
+/// Small and fast pseudo-random number generator (SplitMix64), good enough for inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as i64
+    }
+
+    /// `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// `n` lowercase letters.
+    fn word(&mut self, n: usize) -> String {
+        (0..n).map(|_| (b'a' + self.below(26) as u8) as char).collect()
+    }
+
+    /// A word of 3 to 8 letters, for file names.
+    fn name(&mut self) -> String {
+        let n = self.range(3..=8) as usize;
+        self.word(n)
+    }
+}
+
+pub struct Generator {
+    /// Size of the inputs when none is given, close to the puzzle inputs.
+    pub default_size: usize,
+    /// What the size counts.
+    pub unit: &'static str,
+    generate: fn(&mut Rng, usize) -> String,
+}
+
+impl Generator {
+    pub fn generate(&self, seed: u64, size: usize) -> String {
+        (self.generate)(&mut Rng::new(seed), size)
+    }
+}
+
+const fn generator(default_size: usize, unit: &'static str, generate: fn(&mut Rng, usize) -> String) -> Generator {
+    Generator { default_size, unit, generate }
+}
+
+pub const GENERATORS: [Generator; 25] = [
+    generator(250, "elves", calories),
+    generator(2500, "rounds", strategy_guide),
+    generator(300, "rucksacks", rucksacks),
+    generator(1000, "pairs", section_pairs),
+    generator(500, "moves", crate_moves),
+    generator(4096, "characters", datastream),
+    generator(200, "directories", terminal_output),
+    generator(99, "trees per side", tree_heights),
+    generator(2000, "motions", rope_motions),
+    generator(140, "instructions", cpu_program),
+    generator(8, "monkeys", monkey_notes),
+    generator(170, "columns", heightmap),
+    generator(150, "pairs", packet_pairs),
+    generator(150, "rock paths", rock_paths),
+    generator(5, "sensors per side", sensor_report),
+    generator(60, "valves", valve_scan),
+    generator(10091, "jets", jet_pattern),
+    generator(2000, "cubes", lava_cubes),
+    generator(30, "blueprints", blueprints),
+    generator(5000, "numbers", encrypted_file),
+    generator(2000, "monkeys", monkey_jobs),
+    generator(4000, "path instructions", monkey_map),
+    generator(70, "tiles per side", elf_positions),
+    generator(100, "columns", blizzard_valley),
+    generator(120, "numbers", snafu_numbers),
+];
+
+/// The generator of `day`, from 1 to 25.
+pub fn generator_for(day: u8) -> Option<&'static Generator> {
+    GENERATORS.get(usize::from(day).checked_sub(1)?)
+}
+
+fn lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().fold(String::new(), |mut s, l| {
+        s.push_str(&l);
+        s.push('\n');
+        s
+    })
+}
+
+fn calories(rng: &mut Rng, size: usize) -> String {
+    let elves = (0..size.max(3)).map(|_| {
+        let items = rng.range(1..=14);
+        (0..items).map(|_| rng.range(1000..=60000).to_string()).collect::<Vec<_>>().join("\n")
+    });
+    elves.collect::<Vec<_>>().join("\n\n") + "\n"
+}
+
+fn strategy_guide(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| format!("{} {}", rng.pick(&['A', 'B', 'C']), rng.pick(&['X', 'Y', 'Z']))))
+}
+
+fn rucksacks(rng: &mut Rng, size: usize) -> String {
+    let mut letters = ('a'..='z').chain('A'..='Z').collect::<Vec<_>>();
+    let mut rucksacks = Vec::new();
+    for _ in 0..(size / 3).max(1) {
+        // the badge is the only item in all three rucksacks, which otherwise have their own 17 items,
+        // one of them in both compartments
+        rng.shuffle(&mut letters);
+        let badge = letters[0];
+        for items in letters[1..].chunks(17) {
+            let mut items = items.to_vec();
+            rng.shuffle(&mut items);
+            let n = rng.range(4..=16) as usize;
+            let mut left = vec![items[0], badge];
+            left.extend((2..n).map(|_| *rng.pick(&items[1..9])));
+            let mut right = vec![items[0]];
+            right.extend((1..n).map(|_| *rng.pick(&items[9..])));
+            rng.shuffle(&mut left);
+            rng.shuffle(&mut right);
+            rucksacks.push(left.into_iter().chain(right).collect());
+        }
+    }
+    lines(rucksacks)
+}
+
+fn section_pairs(rng: &mut Rng, size: usize) -> String {
+    let mut range = || {
+        let (a, b) = (rng.range(1..=99), rng.range(1..=99));
+        format!("{}-{}", a.min(b), a.max(b))
+    };
+    lines((0..size).map(|_| format!("{},{}", range(), range())))
+}
+
+fn crate_moves(rng: &mut Rng, size: usize) -> String {
+    const STACKS: usize = 9;
+    let mut stacks = (0..STACKS)
+        .map(|_| (0..rng.range(2..=8)).map(|_| (b'A' + rng.below(26) as u8) as char).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut s = String::new();
+    for level in (0..stacks.iter().map(Vec::len).max().unwrap()).rev() {
+        let row = stacks.iter().map(|stack| stack.get(level).map_or("   ".into(), |c| format!("[{c}]")));
+        writeln!(s, "{}", row.collect::<Vec<_>>().join(" ").trim_end()).unwrap();
+    }
+    writeln!(s, "{}\n", (1..=STACKS).map(|n| format!(" {n} ")).collect::<Vec<_>>().join(" ")).unwrap();
+
+    // moves always leave a crate on their stack so that every stack has a top crate in the end
+    for _ in 0..size {
+        let candidates = (0..STACKS).filter(|&i| stacks[i].len() > 1).collect::<Vec<_>>();
+        let from = *rng.pick(&candidates);
+        let to = (from + 1 + rng.below(STACKS - 1)) % STACKS;
+        let num = rng.range(1..=stacks[from].len() as i64 - 1) as usize;
+        let at = stacks[from].len() - num;
+        let moved = stacks[from].split_off(at);
+        stacks[to].extend(moved);
+        writeln!(s, "move {num} from {} to {}", from + 1, to + 1).unwrap();
+    }
+    s
+}
+
+fn datastream(rng: &mut Rng, size: usize) -> String {
+    // a stretch with only 3 different letters can't hold a marker, then comes a message marker
+    let mut alphabet = ('a'..='z').collect::<Vec<_>>();
+    rng.shuffle(&mut alphabet);
+    let prefix = size.max(28) / 2;
+    let mut s = (0..prefix).map(|_| *rng.pick(&alphabet[..3])).collect::<String>();
+    rng.shuffle(&mut alphabet);
+    s.extend(&alphabet[..14]);
+    s.extend((s.len()..size).map(|_| *rng.pick(&alphabet)));
+    s + "\n"
+}
+
+fn terminal_output(rng: &mut Rng, size: usize) -> String {
+    struct Dir {
+        name: String,
+        dirs: Vec<usize>,
+        files: Vec<(String, u64)>,
+    }
+
+    let mut dirs = vec![Dir { name: "/".into(), dirs: Vec::new(), files: Vec::new() }];
+    for i in 1..size.max(1) {
+        let parent = rng.below(i);
+        let name = loop {
+            let name = rng.name();
+            if dirs[parent].dirs.iter().all(|&d| dirs[d].name != name) {
+                break name;
+            }
+        };
+        dirs.push(Dir { name, dirs: Vec::new(), files: Vec::new() });
+        dirs[parent].dirs.push(i);
+    }
+
+    // the disk is between 40 and 68 million full, so that a directory has to be deleted in part 2,
+    // with file sizes skewed so that some directories are under the 100000 of part 1
+    let mut weights = Vec::new();
+    for (d, dir) in dirs.iter_mut().enumerate() {
+        for _ in 0..rng.range(if d == 0 { 1 } else { 0 }..=4) {
+            let name = match rng.below(3) {
+                0 => rng.name(),
+                _ => format!("{}.{}", rng.name(), rng.word(3)),
+            };
+            let w = rng.range(1..=1000).pow(3) as u64;
+            weights.push(w);
+            dir.files.push((name, w));
+        }
+    }
+    let total = rng.range(41_000_000..=68_000_000) as u64;
+    let sum = weights.iter().sum::<u64>();
+    for dir in &mut dirs {
+        for file in &mut dir.files {
+            file.1 = (file.1 * total / sum).max(1);
+        }
+    }
+
+    fn visit(dirs: &[Dir], d: usize, rng: &mut Rng, s: &mut String) {
+        let dir = &dirs[d];
+        writeln!(s, "$ cd {}\n$ ls", dir.name).unwrap();
+        let mut entries = dir.dirs.iter().map(|&c| format!("dir {}", dirs[c].name))
+            .chain(dir.files.iter().map(|(name, size)| format!("{size} {name}")))
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut entries);
+        entries.iter().for_each(|e| writeln!(s, "{e}").unwrap());
+        for &c in &dir.dirs {
+            visit(dirs, c, rng, s);
+            writeln!(s, "$ cd ..").unwrap();
+        }
+    }
+    let mut s = String::new();
+    visit(&dirs, 0, rng, &mut s);
+    s
+}
+
+fn tree_heights(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    lines((0..size).map(|_| (0..size).map(|_| (b'0' + rng.below(10) as u8) as char).collect()))
+}
+
+fn rope_motions(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| format!("{} {}", rng.pick(&['L', 'R', 'U', 'D']), rng.range(1..=19))))
+}
+
+fn cpu_program(rng: &mut Rng, size: usize) -> String {
+    // keep the sprite on the screen most of the time
+    let mut x = 1;
+    lines((0..size).map(|_| {
+        if rng.chance(0.3) {
+            return "noop".into();
+        }
+        let v = loop {
+            let v = rng.range(-15..=15);
+            if v != 0 && (-1..=40).contains(&(x + v)) {
+                break v;
+            }
+        };
+        x += v;
+        format!("addx {v}")
+    }))
+}
+
+fn monkey_notes(rng: &mut Rng, size: usize) -> String {
+    // the divisors are distinct primes so that part 2 can work modulo their product, which must stay
+    // under 2^32 for the squares of the worry levels to fit in a u64, hence at most 9 monkeys
+    const PRIMES: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    let n = size.clamp(2, PRIMES.len());
+
+    struct Monkey {
+        items: Vec<u64>,
+        /// `None` for `old * old`.
+        mul: Option<u64>,
+        add: u64,
+        divisor: u64,
+        targets: [usize; 2],
+    }
+
+    loop {
+        let mut primes = PRIMES;
+        rng.shuffle(&mut primes);
+        let square = rng.below(n);
+        let monkeys = (0..n)
+            .map(|i| {
+                let (mul, add) = match i {
+                    _ if i == square => (None, 0),
+                    _ if rng.chance(0.5) => (Some(rng.range(2..=19) as u64), 0),
+                    _ => (Some(1), rng.range(1..=8) as u64),
+                };
+                let t = (i + 1 + rng.below(n - 1)) % n;
+                let f = (0..n).filter(|&f| f != i && (f != t || n == 2)).collect::<Vec<_>>();
+                Monkey {
+                    items: (0..rng.range(1..=6)).map(|_| rng.range(50..=99) as u64).collect(),
+                    mul,
+                    add,
+                    divisor: primes[i],
+                    targets: [t, *rng.pick(&f)],
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // the worry levels aren't bounded in part 1, start again if they overflow
+        let mut items = monkeys.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
+        let fits = (0..20 * n).all(|turn| {
+            let m = &monkeys[turn % n];
+            std::mem::take(&mut items[turn % n]).into_iter().all(|old| {
+                let Some(new) = old.checked_mul(m.mul.unwrap_or(old)).and_then(|w| w.checked_add(m.add)) else {
+                    return false;
+                };
+                let new = new / 3;
+                items[m.targets[(new % m.divisor != 0) as usize]].push(new);
+                true
+            })
+        });
+        if !fits {
+            continue;
+        }
+
+        let notes = monkeys.iter().enumerate().map(|(i, m)| {
+            let items = m.items.iter().map(u64::to_string).collect::<Vec<_>>().join(", ");
+            let operation = match m.mul {
+                None => "old * old".into(),
+                Some(1) => format!("old + {}", m.add),
+                Some(k) => format!("old * {k}"),
+            };
+            format!(
+                "Monkey {i}:\n  Starting items: {items}\n  Operation: new = {operation}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                m.divisor, m.targets[0], m.targets[1],
+            )
+        });
+        return notes.collect::<Vec<_>>().join("\n");
+    }
+}
+
+fn heightmap(rng: &mut Rng, size: usize) -> String {
+    // a hill peaking at E, gentle enough to be climbed straight from S, which is far enough to be at
+    // the lowest elevation, with random dips everywhere except on that straight path
+    let (w, h) = (size.max(40) as i64, (size as i64 / 4).max(5));
+    let end = vec2(w * 3 / 4, rng.range(0..=h - 1));
+    let start = vec2(0, rng.range(0..=h - 1));
+    let step = start.manhattan(end) / 26;
+    let on_path = |p: Vec2<i64>| {
+        (p.y == start.y && p.x <= end.x) || (p.x == end.x && (start.y.min(end.y)..=start.y.max(end.y)).contains(&p.y))
+    };
+    lines((0..h).map(|y| {
+        (0..w).map(|x| {
+            let p = vec2(x, y);
+            let mut height = (25 - p.manhattan(end) / step).max(0);
+            if !on_path(p) && rng.chance(0.2) {
+                height = (height - rng.range(1..=3)).max(0);
+            }
+            match p {
+                _ if p == start => 'S',
+                _ if p == end => 'E',
+                _ => (b'a' + height as u8) as char,
+            }
+        }).collect::<String>()
+    }))
+}
+
+fn packet_pairs(rng: &mut Rng, size: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> String {
+        let items = (0..rng.range(0..=5)).map(|_| {
+            if depth < 4 && rng.chance(0.3) { packet(rng, depth + 1) } else { rng.range(0..=10).to_string() }
+        });
+        format!("[{}]", items.collect::<Vec<_>>().join(","))
+    }
+
+    let pairs = (0..size).map(|_| {
+        let a = packet(rng, 0);
+        let b = loop {
+            let b = packet(rng, 0);
+            if b != a {
+                break b;
+            }
+        };
+        format!("{a}\n{b}\n")
+    });
+    pairs.collect::<Vec<_>>().join("\n")
+}
+
+fn rock_paths(rng: &mut Rng, size: usize) -> String {
+    // cups and shelves apart from each other like in the puzzle inputs, which also repeat some paths:
+    // overlapping paths could enclose the source and keep the sand from ever falling into the abyss
+    let mut boxes: Vec<(Vec2<i64>, Vec2<i64>)> = Vec::new();
+    let mut paths = Vec::new();
+    for _ in 0..size.div_ceil(2) * 50 {
+        if paths.len() >= size.div_ceil(2) {
+            break;
+        }
+        let (min, w, d) = (vec2(rng.range(440..=550), rng.range(13..=170)), rng.range(2..=12), rng.range(2..=8));
+        let max = min + vec2(w, d);
+        if boxes.iter().any(|(a, b)| min.x <= b.x + 2 && a.x <= max.x + 2 && min.y <= b.y + 2 && a.y <= max.y + 2) {
+            continue;
+        }
+        boxes.push((min, max));
+        let points = match rng.below(3) {
+            0 => vec![vec2(min.x, max.y), max],
+            _ => vec![min, vec2(min.x, max.y), max, vec2(max.x, min.y + rng.range(0..=d - 1))],
+        };
+        paths.push(points.iter().map(|p| format!("{},{}", p.x, p.y)).collect::<Vec<_>>().join(" -> "));
+    }
+    let mut repeated = (0..size.max(1)).map(|i| paths[i % paths.len()].clone()).collect::<Vec<_>>();
+    rng.shuffle(&mut repeated);
+    lines(repeated)
+}
+
+fn sensor_report(rng: &mut Rng, size: usize) -> String {
+    // a jittered lattice of sensors covers the whole search area, then the sensors near the distress
+    // beacon are shrunk to leave it out, and 4 diagonal sensors cover everything else near it
+    const SIZE: i64 = 4_000_000;
+    let n = size.max(2) as i64;
+    let a = (SIZE + n - 2) / (n - 1);
+    let beacon = vec2(rng.range(0..=SIZE), rng.range(0..=SIZE));
+    let k = 3 * a;
+
+    let mut sensors = Vec::new();
+    for i in 0..n {
+        for j in 0..n {
+            let pos = vec2(i * a + rng.range(-a / 8..=a / 8), j * a + rng.range(-a / 8..=a / 8));
+            let radius = (a + a / 4 + rng.range(0..=a / 4)).min(pos.manhattan(beacon) - 1);
+            sensors.push((pos, radius));
+        }
+    }
+    for d in [vec2(1, 1), vec2(1, -1), vec2(-1, 1), vec2(-1, -1)] {
+        sensors.push((beacon + d * k, 2 * k - 1));
+    }
+    rng.shuffle(&mut sensors);
+
+    lines(sensors.into_iter().map(|(pos, radius)| {
+        let dx = rng.range(0..=radius);
+        let dy = radius - dx;
+        let closest = pos + vec2(dx * if rng.chance(0.5) { 1 } else { -1 }, dy * if rng.chance(0.5) { 1 } else { -1 });
+        format!("Sensor at x={}, y={}: closest beacon is at x={}, y={}", pos.x, pos.y, closest.x, closest.y)
+    }))
+}
+
+fn valve_scan(rng: &mut Rng, size: usize) -> String {
+    let n = size.clamp(2, 26 * 26);
+    let mut names = HashSet::from(["AA".to_string()]);
+    while names.len() < n {
+        names.insert((0..2).map(|_| (b'A' + rng.below(26) as u8) as char).collect());
+    }
+    let mut names = names.into_iter().collect::<Vec<_>>();
+    names.sort();
+    rng.shuffle(&mut names[1..]);
+
+    // a random tree with a few more tunnels, and working valves (at most 15 like the puzzle inputs,
+    // part 2 being exponential in their number) anywhere but at AA
+    let mut tunnels = vec![HashSet::new(); n];
+    let mut connect = |a: usize, b: usize| {
+        if a != b {
+            tunnels[a].insert(b);
+            tunnels[b].insert(a);
+        }
+    };
+    for i in 1..n {
+        connect(i, rng.below(i));
+    }
+    for _ in 0..n / 4 {
+        connect(rng.below(n), rng.below(n));
+    }
+    let mut working = (1..n).collect::<Vec<_>>();
+    rng.shuffle(&mut working);
+    working.truncate((n / 4).clamp(1, 15));
+
+    let mut scan = (0..n).map(|i| {
+        let rate = if working.contains(&i) { rng.range(3..=25) } else { 0 };
+        let mut to = tunnels[i].iter().map(|&t| names[t].as_str()).collect::<Vec<_>>();
+        to.sort();
+        let tunnels = match to.as_slice() {
+            [t] => format!("tunnel leads to valve {t}"),
+            _ => format!("tunnels lead to valves {}", to.join(", ")),
+        };
+        format!("Valve {} has flow rate={rate}; {tunnels}", names[i])
+    }).collect::<Vec<_>>();
+    rng.shuffle(&mut scan);
+    lines(scan)
+}
+
+fn jet_pattern(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1)).map(|_| *rng.pick(&['<', '>'])).collect::<String>() + "\n"
+}
+
+fn lava_cubes(rng: &mut Rng, size: usize) -> String {
+    // a rough ball, with pockets of air inside
+    let center = vec3(10, 10, 10);
+    let mut cubes = HashSet::new();
+    let mut cubes_in_order = Vec::new();
+    while cubes.len() < size.min(3000) {
+        let c = vec3(rng.range(0..=20), rng.range(0..=20), rng.range(0..=20));
+        let d = c - center;
+        if d.x * d.x + d.y * d.y + d.z * d.z <= 100 && cubes.insert(c) {
+            cubes_in_order.push(c);
+        }
+    }
+    lines(cubes_in_order.into_iter().map(|c| format!("{},{},{}", c.x, c.y, c.z)))
+}
+
+fn blueprints(rng: &mut Rng, size: usize) -> String {
+    lines((1..=size.max(1)).map(|i| {
+        format!(
+            "Blueprint {i}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+            rng.range(2..=4), rng.range(2..=4), rng.range(2..=4), rng.range(5..=20), rng.range(2..=4), rng.range(7..=20),
+        )
+    }))
+}
+
+fn encrypted_file(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = (0..size.max(1)).map(|_| loop {
+        let n = rng.range(-10_000..=10_000);
+        if n != 0 {
+            break n;
+        }
+    }).collect::<Vec<_>>();
+    let zero = rng.below(numbers.len());
+    numbers[zero] = 0;
+    lines(numbers.into_iter().map(|n| n.to_string()))
+}
+
+fn monkey_jobs(rng: &mut Rng, size: usize) -> String {
+    /// Jobs of the troop being built, with the names used so far.
+    struct Troop {
+        jobs: Vec<String>,
+        names: HashSet<String>,
+    }
+
+    impl Troop {
+        fn name(&mut self, rng: &mut Rng) -> String {
+            loop {
+                let name = rng.word(4);
+                if name != "root" && name != "humn" && self.names.insert(name.clone()) {
+                    return name;
+                }
+            }
+        }
+
+        fn job(&mut self, rng: &mut Rng, job: String) -> String {
+            let name = self.name(rng);
+            self.jobs.push(format!("{name}: {job}"));
+            name
+        }
+
+        /// A random tree of about `budget` monkeys, with its value. Divisions are always exact.
+        fn tree(&mut self, rng: &mut Rng, budget: usize) -> (String, i64) {
+            if budget < 3 {
+                let value = rng.range(1..=20);
+                return (self.job(rng, value.to_string()), value);
+            }
+            let left_budget = 1 + rng.below(budget - 2);
+            let (l, a) = self.tree(rng, left_budget);
+            let (r, b) = self.tree(rng, budget - 1 - left_budget);
+            let (op, value) = match rng.below(4) {
+                0 if a.checked_mul(b).is_some_and(|v| v.abs() < 1_000_000) => ('*', a * b),
+                1 if b != 0 && a % b == 0 => ('/', a / b),
+                2 => ('-', a - b),
+                _ => ('+', a + b),
+            };
+            (self.job(rng, format!("{l} {op} {r}")), value)
+        }
+
+        /// A random tree of about `budget` monkeys whose value is `value`.
+        fn tree_with_value(&mut self, rng: &mut Rng, budget: usize, value: i64) -> String {
+            let (name, v) = self.tree(rng, budget.saturating_sub(2));
+            let diff = self.job(rng, (value - v).abs().to_string());
+            self.job(rng, format!("{name} {} {diff}", if value >= v { '+' } else { '-' }))
+        }
+    }
+
+    fn gcd(a: i64, b: i64) -> i64 {
+        if b == 0 { a.abs() } else { gcd(b, a % b) }
+    }
+
+    // the monkeys from humn to root compute a * humn + b, whose coefficients are kept integer so that
+    // the divisions are exact whatever humn yells, and whose value is known for the answer of part 2
+    const LIMIT: i64 = 10_000_000_000;
+    let size = size.max(10);
+    let mut troop = Troop { jobs: Vec::new(), names: HashSet::from(["root".into(), "humn".into()]) };
+    let depth = (size / 30).clamp(1, 70);
+    let budget = (size - depth) / (depth + 1);
+    let (mut a, mut b) = (1i64, 0i64);
+    let mut chain = "humn".to_string();
+    for _ in 0..depth {
+        let divisor = (2..=9).rev().find(|d| gcd(a, b) % d == 0);
+        chain = match (rng.below(4), divisor) {
+            (0, Some(d)) => {
+                (a, b) = (a / d, b / d);
+                let d = troop.tree_with_value(rng, budget, d);
+                troop.job(rng, format!("{chain} / {d}"))
+            },
+            (1, _) if (a.abs() + b.abs()) * 9 < LIMIT => {
+                let k = rng.range(2..=9);
+                (a, b) = (a * k, b * k);
+                let k = troop.tree_with_value(rng, budget, k);
+                let job = if rng.chance(0.5) { format!("{chain} * {k}") } else { format!("{k} * {chain}") };
+                troop.job(rng, job)
+            },
+            (2, _) => {
+                let (t, v) = troop.tree(rng, budget);
+                if rng.chance(0.5) {
+                    b -= v;
+                    troop.job(rng, format!("{chain} - {t}"))
+                } else {
+                    (a, b) = (-a, v - b);
+                    troop.job(rng, format!("{t} - {chain}"))
+                }
+            },
+            _ => {
+                let (t, v) = troop.tree(rng, budget);
+                b += v;
+                let job = if rng.chance(0.5) { format!("{chain} + {t}") } else { format!("{t} + {chain}") };
+                troop.job(rng, job)
+            },
+        };
+    }
+    let answer = rng.range(1000..=1_000_000);
+    let other = troop.tree_with_value(rng, budget, a * answer + b);
+    troop.jobs.push(format!("root: {chain} + {other}"));
+    troop.jobs.push(format!("humn: {}", rng.range(1..=5000)));
+    rng.shuffle(&mut troop.jobs);
+    lines(troop.jobs)
+}
+
+fn monkey_map(rng: &mut Rng, size: usize) -> String {
+    // the faces are laid out like in the puzzle inputs, which part 2 relies on
+    const FACE: usize = 50;
+    const NET: [&str; 4] = [" ##", " # ", "## ", "#  "];
+    let mut s = String::new();
+    for (y, net_row) in NET.iter().flat_map(|r| [r; FACE]).enumerate() {
+        let row = net_row.bytes().flat_map(|f| [f; FACE]).enumerate().map(|(x, f)| match f {
+            b' ' => ' ',
+            _ if (x, y) == (FACE, 0) => '.',
+            _ if rng.chance(0.1) => '#',
+            _ => '.',
+        });
+        writeln!(s, "{}", row.collect::<String>().trim_end()).unwrap();
+    }
+    s.push('\n');
+    for i in 0..size.max(1) {
+        if i > 0 {
+            s.push(*rng.pick(&['L', 'R']));
+        }
+        write!(s, "{}", rng.range(1..=50)).unwrap();
+    }
+    s + "\n"
+}
+
+fn elf_positions(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    loop {
+        let rows = (0..size).map(|_| (0..size).map(|_| if rng.chance(0.5) { '#' } else { '.' }).collect::<String>());
+        let input = lines(rows);
+        // a few layouts make the elves move around in circles forever
+        let map = day23::parse(&input).unwrap();
+        if !map.is_empty() && day23::last_round(&map, None).is_some() {
+            return input;
+        }
+    }
+}
+
+fn blizzard_valley(rng: &mut Rng, size: usize) -> String {
+    let (w, h) = (size.max(3), (size * 7 / 20).max(2));
+    loop {
+        // no blizzard goes up or down the columns of the entrance and the exit, they would leave the
+        // valley through them
+        let valley = (0..h).map(|_| {
+            (0..w).map(|x| match rng.below(8) {
+                0 => '>',
+                1 => '<',
+                2 | 3 if x == 0 || x == w - 1 => '.',
+                2 => '^',
+                3 => 'v',
+                _ => '.',
+            }).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        if can_cross(&valley) {
+            let wall = "#".repeat(w);
+            let rows = valley.iter().map(|r| format!("#{}#", r.iter().collect::<String>()));
+            return lines([format!("#.{wall}")].into_iter().chain(rows).chain([format!("{wall}.#")]));
+        }
+    }
+}
+
+/// Whether the expedition can go from the entrance to the exit, back and to the exit again, the
+/// blizzards being the same every `lcm(width, height)` minutes.
+fn can_cross(valley: &[Vec<char>]) -> bool {
+    let (w, h) = (valley[0].len() as i64, valley.len() as i64);
+    let period = (1..).map(|n| n * w).find(|n| n % h == 0).unwrap();
+    let free = |p: Vec2<i64>, t: i64| {
+        if p == vec2(0, -1) || p == vec2(w - 1, h) {
+            return true;
+        }
+        if p.x < 0 || p.y < 0 || p.x >= w || p.y >= h {
+            return false;
+        }
+        let at = |x: i64, y: i64| valley[y.rem_euclid(h) as usize][x.rem_euclid(w) as usize];
+        at(p.x - t, p.y) != '>' && at(p.x + t, p.y) != '<' && at(p.x, p.y - t) != 'v' && at(p.x, p.y + t) != '^'
+    };
+    let goals = [vec2(w - 1, h), vec2(0, -1), vec2(w - 1, h)];
+    let successors = |&(p, t, trip): &(Vec2<i64>, i64, usize)| {
+        let trip = trip + (p == goals[trip]) as usize;
+        let next = (t + 1) % period;
+        [vec2(0, 0), vec2(1, 0), vec2(-1, 0), vec2(0, 1), vec2(0, -1)]
+            .into_iter()
+            .map(move |d| p + d)
+            .filter(move |&q| trip < goals.len() && free(q, next))
+            .map(move |q| (q, next, trip))
+            .collect::<Vec<_>>()
+    };
+    search::bfs((vec2(0, -1), 0, 0), successors, |&(p, _, trip)| trip == goals.len() - 1 && p == goals[trip]).path.is_some()
+}
+
+fn snafu_numbers(rng: &mut Rng, size: usize) -> String {
+    lines((0..size.max(1)).map(|_| {
+        let digits = rng.range(1..=12) as u32;
+        let mut n = rng.range(1..=10i64.pow(digits));
+        let mut digits = Vec::new();
+        while n != 0 {
+            let (digit, carry) = match n % 5 {
+                3 => ('=', 1),
+                4 => ('-', 1),
+                d => ((b'0' + d as u8) as char, 0),
+            };
+            digits.push(digit);
+            n = n / 5 + carry;
+        }
+        digits.iter().rev().collect::<String>()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DAYS;
+
+    #[test]
+    fn test_generators() {
+        assert_eq!(GENERATORS[0].generate(7, 10), GENERATORS[0].generate(7, 10));
+        assert_ne!(GENERATORS[0].generate(7, 10), GENERATORS[0].generate(8, 10));
+
+        // small inputs that every solution solves, except for the days too slow without optimisations
+        // whatever the size of their input
+        let sizes = [20, 30, 30, 20, 20, 100, 20, 10, 30, 140, 4, 40, 10, 10, 3, 8, 500, 200, 2, 50, 40, 30, 10, 10, 10];
+        for (day, size) in DAYS.iter().zip(sizes) {
+            for seed in 0..3 {
+                let input = GENERATORS[day.number as usize - 1].generate(seed, size);
+                let parsed = day.solution.parse(&input).unwrap_or_else(|err| panic!("day {}, seed {seed}: {err}", day.number));
+                if ![14, 15, 16, 19].contains(&day.number) {
+                    day.solution.part1(&*parsed);
+                    day.solution.part2(&*parsed);
+                }
+            }
+        }
+    }
+}
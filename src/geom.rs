@@ -0,0 +1,254 @@
+//! 2D and 3D vectors used as positions and offsets, bounding boxes and compass directions.
+//!
+//! In 2D `y` grows downwards like the rows of the inputs, so [`Dir::N`] is `(0, -1)`.
+
+use std::fmt;
+use std::ops;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+pub const fn vec2<T>(x: T, y: T) -> Vec2<T> {
+    Vec2 { x, y }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+pub const fn vec3<T>(x: T, y: T, z: T) -> Vec3<T> {
+    Vec3 { x, y, z }
+}
+
+/// Implements the component-wise operators and distances of a vector type with the given fields.
+macro_rules! impl_vector {
+    ($name:ident { $($field:ident),+ }) => {
+        impl<T: ops::Add<Output = T>> ops::Add for $name<T> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                $name { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl<T: ops::Sub<Output = T>> ops::Sub for $name<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                $name { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl<T: ops::Neg<Output = T>> ops::Neg for $name<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $name { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: Copy + ops::Mul<Output = T>> ops::Mul<T> for $name<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self {
+                $name { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl<T: Copy + ops::Div<Output = T>> ops::Div<T> for $name<T> {
+            type Output = Self;
+
+            fn div(self, rhs: T) -> Self {
+                $name { $($field: self.$field / rhs),+ }
+            }
+        }
+
+        impl<T: Copy + ops::Add<Output = T>> ops::AddAssign for $name<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: Copy + ops::Sub<Output = T>> ops::SubAssign for $name<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<T: Copy + ops::Mul<Output = T>> ops::MulAssign<T> for $name<T> {
+            fn mul_assign(&mut self, rhs: T) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<T: Copy + Ord + ops::Add<Output = T> + ops::Sub<Output = T>> $name<T> {
+            /// Sum of the distances along each axis.
+            pub fn manhattan(self, other: Self) -> T {
+                let d = [$(self.$field.max(other.$field) - self.$field.min(other.$field)),+];
+                d.into_iter().reduce(|a, b| a + b).unwrap()
+            }
+
+            /// Largest of the distances along each axis, the number of king moves between the two.
+            pub fn chebyshev(self, other: Self) -> T {
+                let d = [$(self.$field.max(other.$field) - self.$field.min(other.$field)),+];
+                d.into_iter().max().unwrap()
+            }
+        }
+
+        impl<T: Copy + Ord> ComponentWise for $name<T> {
+            fn min(self, other: Self) -> Self {
+                $name { $($field: self.$field.min(other.$field)),+ }
+            }
+
+            fn max(self, other: Self) -> Self {
+                $name { $($field: self.$field.max(other.$field)),+ }
+            }
+        }
+
+        impl Bounds<$name<i64>> {
+            /// Number of positions along each axis.
+            pub fn size(&self) -> $name<i64> {
+                $name { $($field: self.max.$field - self.min.$field + 1),+ }
+            }
+
+            /// The bounds grown by `n` in every direction.
+            pub fn grow(self, n: i64) -> Self {
+                Self { min: $name { $($field: self.min.$field - n),+ }, max: $name { $($field: self.max.$field + n),+ } }
+            }
+        }
+    };
+}
+
+impl_vector!(Vec2 { x, y });
+impl_vector!(Vec3 { x, y, z });
+
+impl<T: fmt::Display> fmt::Display for Vec2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vec3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// Minimum and maximum of each component, what [`Bounds`] needs from the vectors.
+pub trait ComponentWise: Copy {
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+}
+
+/// Smallest box, inclusive on both ends, containing a set of points.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Bounds<V> {
+    pub min: V,
+    pub max: V,
+}
+
+impl<V: ComponentWise + PartialEq> Bounds<V> {
+    pub fn new(p: V) -> Self {
+        Self { min: p, max: p }
+    }
+
+    /// Bounds of `points`, or `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = V>) -> Option<Self> {
+        points.into_iter().fold(None, |b: Option<Self>, p| Some(match b {
+            Some(b) => b.extend(p),
+            None => Self::new(p),
+        }))
+    }
+
+    /// The bounds grown to contain `p`.
+    pub fn extend(self, p: V) -> Self {
+        Self { min: self.min.min(p), max: self.max.max(p) }
+    }
+
+    pub fn contains(&self, p: V) -> bool {
+        self.min.max(p) == p && self.max.min(p) == p
+    }
+}
+
+/// Compass direction, in clockwise order from east.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Dir { E, SE, S, SW, W, NW, N, NE }
+
+impl Dir {
+    /// Every direction, clockwise from east.
+    pub const ALL: [Dir; 8] = [Dir::E, Dir::SE, Dir::S, Dir::SW, Dir::W, Dir::NW, Dir::N, Dir::NE];
+    /// The 4 orthogonal directions, clockwise from east.
+    pub const ORTHOGONAL: [Dir; 4] = [Dir::E, Dir::S, Dir::W, Dir::N];
+
+    /// The direction rotated clockwise by `eighths` eighths of a turn (anticlockwise if negative).
+    pub fn rotate(self, eighths: i32) -> Self {
+        Self::ALL[(self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(-2)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.rotate(4)
+    }
+
+    /// Offset of one step in this direction.
+    pub fn vector<T: From<i8>>(self) -> Vec2<T> {
+        let (x, y) = match self {
+            Dir::E => (1, 0),
+            Dir::SE => (1, 1),
+            Dir::S => (0, 1),
+            Dir::SW => (-1, 1),
+            Dir::W => (-1, 0),
+            Dir::NW => (-1, -1),
+            Dir::N => (0, -1),
+            Dir::NE => (1, -1),
+        };
+        vec2(T::from(x), T::from(y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        let mut a = vec2(3, -2);
+        a += vec2(1, 1);
+        assert_eq!(a, vec2(4, -1));
+        assert_eq!(-a * 2 - vec2(1, 1), vec2(-9, 1));
+        assert_eq!(a.manhattan(vec2(0, 2)), 7);
+        assert_eq!(a.chebyshev(vec2(0, 2)), 4);
+        assert_eq!(vec3(1u8, 5, 2).manhattan(vec3(3, 2, 2)), 5);
+
+        let bounds = Bounds::from_points([vec3(1, 5, 2), vec3(3, 2, 2), vec3(2, 2, 7)]).unwrap();
+        assert_eq!(bounds, Bounds { min: vec3(1, 2, 2), max: vec3(3, 5, 7) });
+        assert_eq!(bounds.size(), vec3(3, 4, 6));
+        assert!(bounds.contains(vec3(2, 3, 4)));
+        assert!(!bounds.contains(vec3(0, 3, 4)));
+        assert!(bounds.grow(1).contains(vec3(0, 3, 4)));
+        assert_eq!(Bounds::<Vec2<i32>>::from_points([]), None);
+    }
+
+    #[test]
+    fn test_dir() {
+        assert_eq!(Dir::N.turn_right(), Dir::E);
+        assert_eq!(Dir::E.turn_left(), Dir::N);
+        assert_eq!(Dir::NW.rotate(3), Dir::E);
+        assert_eq!(Dir::S.opposite(), Dir::N);
+        assert_eq!(Dir::SW.vector::<i64>(), vec2(-1, 1));
+        assert!(Dir::ALL.iter().all(|d| d.vector::<i32>() + d.opposite().vector() == vec2(0, 0)));
+    }
+}
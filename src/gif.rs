@@ -0,0 +1,192 @@
+//! Minimal animated GIF encoder: a global palette, then one LZW-compressed frame per [`Image`],
+//! looping forever.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::image::{Image, Rgb};
+
+/// Largest code of the LZW compression, codes are at most 12 bits wide.
+const MAX_CODE: u16 = 4095;
+
+/// Writes the frames of an animation one by one, all as large as the screen.
+pub struct GifWriter<W: Write> {
+    w: W,
+    width: u16,
+    height: u16,
+    /// The palette has 2^depth colours.
+    depth: u8,
+    /// Hundredths of a second each frame is shown.
+    delay: u16,
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(mut w: W, width: usize, height: usize, palette: &[Rgb], delay: u16) -> io::Result<Self> {
+        assert!(!palette.is_empty() && palette.len() <= 256, "a GIF palette has 1 to 256 colours");
+        let (width, height) = (width as u16, height as u16);
+        let depth = (1..=8).find(|&bits| palette.len() <= 1 << bits).unwrap();
+
+        w.write_all(b"GIF89a")?;
+        w.write_all(&width.to_le_bytes())?;
+        w.write_all(&height.to_le_bytes())?;
+        w.write_all(&[0x80 | (depth - 1), 0, 0])?;
+        for i in 0..1 << depth {
+            let Rgb(r, g, b) = palette.get(i).copied().unwrap_or(Rgb(0, 0, 0));
+            w.write_all(&[r, g, b])?;
+        }
+        // NETSCAPE2.0 application extension, repeating forever
+        w.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        Ok(Self { w, width, height, depth, delay })
+    }
+
+    pub fn frame(&mut self, frame: &Image) -> io::Result<()> {
+        assert_eq!((frame.width, frame.height), (self.width as usize, self.height as usize), "frame of the wrong size");
+        let w = &mut self.w;
+        // graphic control extension with the delay, then the image descriptor covering the screen
+        w.write_all(&[0x21, 0xf9, 4, 0])?;
+        w.write_all(&self.delay.to_le_bytes())?;
+        w.write_all(&[0, 0, 0x2c, 0, 0, 0, 0])?;
+        w.write_all(&self.width.to_le_bytes())?;
+        w.write_all(&self.height.to_le_bytes())?;
+        w.write_all(&[0])?;
+
+        // pixels are LZW-coded with at least 2 bits
+        let min_code_size = self.depth.max(2);
+        w.write_all(&[min_code_size])?;
+        for block in lzw(&frame.pixels, min_code_size).chunks(255) {
+            w.write_all(&[block.len() as u8])?;
+            w.write_all(block)?;
+        }
+        w.write_all(&[0])
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.w.write_all(b";")?;
+        Ok(self.w)
+    }
+}
+
+/// Packs codes of variable width, least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// LZW compression of `pixels` as GIF does it, starting with a clear code and clearing the table
+/// again when it is full.
+fn lzw(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = BitWriter::default();
+    let mut width = min_code_size + 1;
+    let mut table = HashMap::<(u16, u8), u16>::new();
+    let mut next = end + 1;
+    out.write(clear, width);
+
+    let mut prefix: Option<u16> = None;
+    for &p in pixels {
+        let Some(code) = prefix else {
+            prefix = Some(p as u16);
+            continue;
+        };
+        if let Some(&c) = table.get(&(code, p)) {
+            prefix = Some(c);
+            continue;
+        }
+        out.write(code, width);
+        if next > MAX_CODE {
+            out.write(clear, width);
+            table.clear();
+            next = end + 1;
+            width = min_code_size + 1;
+        } else {
+            table.insert((code, p), next);
+            // the decoder widens the codes as soon as the table reaches the next power of 2
+            if next == 1 << width && width < 12 {
+                width += 1;
+            }
+            next += 1;
+        }
+        prefix = Some(p as u16);
+    }
+    if let Some(code) = prefix {
+        out.write(code, width);
+    }
+    out.write(end, width);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the output of [`lzw`].
+    fn unlzw(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut bits = data.iter().flat_map(|b| (0..8).map(move |i| (b >> i) & 1));
+        let mut width = min_code_size + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            let code = (0..width).map(|i| (bits.next().unwrap() as usize) << i).sum::<usize>();
+            if code == clear {
+                table = (0..clear).map(|c| vec![c as u8]).chain([vec![], vec![]]).collect();
+                width = min_code_size + 1;
+                prev = None;
+                continue;
+            } else if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), &prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) => [p.as_slice(), &p[..1]].concat(),
+                (None, None) => panic!("invalid code {code}"),
+            };
+            if let Some(p) = prev {
+                table.push([p.as_slice(), &entry[..1]].concat());
+            }
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            out.extend(&entry);
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        // pseudo-random enough to fill the code table a few times
+        let pixels = (0..50_000u64).map(|i| (i.wrapping_mul(6_364_136_223_846_793_005) >> 61) as u8).collect::<Vec<_>>();
+        assert_eq!(unlzw(&lzw(&pixels, 3), 3), pixels);
+        assert_eq!(unlzw(&lzw(&[1, 1, 1, 1], 2), 2), [1, 1, 1, 1]);
+
+        let mut gif = GifWriter::new(Vec::new(), 3, 2, &[Rgb(0, 0, 0), Rgb(255, 0, 0), Rgb(0, 255, 0)], 10).unwrap();
+        gif.frame(&Image::new(3, 2, |x, y| (x + y) as u8)).unwrap();
+        gif.frame(&Image::new(3, 2, |_, _| 2)).unwrap();
+        let out = gif.finish().unwrap();
+        assert_eq!(out[..13], *b"GIF89a\x03\x00\x02\x00\x81\x00\x00");
+        assert_eq!(out.last(), Some(&b';'));
+    }
+}
@@ -0,0 +1,168 @@
+//! 2D grid of cells shared by the grid-based days.
+//!
+//! Positions are [`Vec2`]s with `y` growing downwards, relative to an [`origin`](Grid::origin)
+//! that is `(0, 0)` unless the grid only covers part of the plane. Rows may have different lengths,
+//! missing cells at the end of a short row are simply outside of the grid.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::geom::{vec2, Dir, Vec2};
+use crate::parser::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: Vec<Vec<T>>,
+    /// Position of the first cell of the first row.
+    origin: Vec2<i64>,
+}
+
+impl<T> Grid<T> {
+    /// A `width`×`height` grid filled with `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Self::from_rows(vec![vec![value; width]; height])
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        Self { rows, origin: vec2(0, 0) }
+    }
+
+    /// Parses every line of `s`, a sub-slice of `input`, as a row with `cell` mapping each byte to
+    /// a cell. Bytes for which it returns `None` are reported as an error expecting `expected`.
+    pub fn parse(input: &str, s: &str, expected: &str, mut cell: impl FnMut(u8) -> Option<T>) -> Result<Self, ParseError> {
+        let rows = s
+            .lines()
+            .map(|l| {
+                l.bytes().enumerate()
+                    .map(|(x, c)| cell(c).ok_or_else(|| ParseError::at(input, &l[x..], expected)))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_rows(rows))
+    }
+
+    /// Moves the grid so that its first cell is at `origin`.
+    pub fn with_origin(self, origin: Vec2<i64>) -> Self {
+        Self { origin, ..self }
+    }
+
+    pub fn origin(&self) -> Vec2<i64> {
+        self.origin
+    }
+
+    /// Length of the longest row.
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Position of the bottom-right corner of the bounding box of the grid.
+    pub fn max(&self) -> Vec2<i64> {
+        self.origin + vec2(self.width() as i64 - 1, self.height() as i64 - 1)
+    }
+
+    /// Indices of the cell at `pos` in `rows`, if it's in the grid.
+    fn indices(&self, pos: Vec2<i64>) -> Option<(usize, usize)> {
+        let x = usize::try_from(pos.x - self.origin.x).ok()?;
+        let y = usize::try_from(pos.y - self.origin.y).ok()?;
+        (x < self.rows.get(y)?.len()).then_some((x, y))
+    }
+
+    pub fn contains(&self, pos: Vec2<i64>) -> bool {
+        self.indices(pos).is_some()
+    }
+
+    pub fn get(&self, pos: Vec2<i64>) -> Option<&T> {
+        self.indices(pos).map(|(x, y)| &self.rows[y][x])
+    }
+
+    pub fn get_mut(&mut self, pos: Vec2<i64>) -> Option<&mut T> {
+        self.indices(pos).map(|(x, y)| &mut self.rows[y][x])
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        self.rows.iter().map(Vec::as_slice)
+    }
+
+    /// Cells of the column at `x`, from top to bottom, skipping rows too short to reach it.
+    pub fn column(&self, x: i64) -> impl DoubleEndedIterator<Item = &T> {
+        let x = usize::try_from(x - self.origin.x).ok();
+        self.rows.iter().filter_map(move |row| row.get(x?))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2<i64>, &T)> {
+        let origin = self.origin;
+        self.rows.iter().zip(origin.y..).flat_map(move |(row, y)| row.iter().zip(origin.x..).map(move |(c, x)| (vec2(x, y), c)))
+    }
+
+    /// Position of the first cell, row by row, matching `predicate`.
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Vec2<i64>> {
+        self.cells().find_map(|(pos, c)| predicate(c).then_some(pos))
+    }
+
+    /// Positions of the orthogonal neighbours of `pos` that are in the grid, clockwise from the right one.
+    pub fn neighbours4(&self, pos: Vec2<i64>) -> impl Iterator<Item = Vec2<i64>> + '_ {
+        self.neighbours(pos, &Dir::ORTHOGONAL)
+    }
+
+    /// Positions of the orthogonal and diagonal neighbours of `pos` that are in the grid, clockwise
+    /// from the right one.
+    pub fn neighbours8(&self, pos: Vec2<i64>) -> impl Iterator<Item = Vec2<i64>> + '_ {
+        self.neighbours(pos, &Dir::ALL)
+    }
+
+    fn neighbours<'a>(&'a self, pos: Vec2<i64>, dirs: &'static [Dir]) -> impl Iterator<Item = Vec2<i64>> + 'a {
+        dirs.iter().map(move |d| pos + d.vector()).filter(|&p| self.contains(p))
+    }
+}
+
+impl<T> Index<Vec2<i64>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Vec2<i64>) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("position {pos} is outside of the grid"))
+    }
+}
+
+impl<T> IndexMut<Vec2<i64>> for Grid<T> {
+    fn index_mut(&mut self, pos: Vec2<i64>) -> &mut T {
+        self.get_mut(pos).unwrap_or_else(|| panic!("position {pos} is outside of the grid"))
+    }
+}
+
+/// Renders one character per cell and one line per row.
+impl<T: Copy + Into<char>> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row.iter().map(|&c| c.into()).collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let input = "ab\ncde\n";
+        let grid = Grid::parse(input, input, "a letter", |c| c.is_ascii_lowercase().then_some(c)).unwrap().with_origin(vec2(-1, 5));
+        assert_eq!((grid.width(), grid.height(), grid.max()), (3, 2, vec2(1, 6)));
+        assert_eq!(grid[vec2(0, 5)], b'b');
+        assert_eq!(grid.get(vec2(1, 5)), None);
+        assert_eq!(grid.get(vec2(1, 6)), Some(&b'e'));
+        assert_eq!(grid.column(1).collect::<Vec<_>>(), [&b'e']);
+        assert_eq!(grid.position(|&c| c == b'd'), Some(vec2(0, 6)));
+        assert_eq!(grid.neighbours4(vec2(0, 5)).collect::<Vec<_>>(), [vec2(0, 6), vec2(-1, 5)]);
+        assert_eq!(grid.neighbours8(vec2(0, 5)).count(), 4);
+        assert_eq!(grid.to_string(), input);
+
+        let err = Grid::parse(input, input, "a letter", |c| (c != b'd').then_some(c)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+    }
+}
@@ -0,0 +1,182 @@
+//! Export of the simulations as images, enabled by `aoc run --export <DIR>`.
+//!
+//! Days render [`Image`]s whose pixels are indices in a palette of their own, with a colour per
+//! kind of tile. A single image is written with [`save`] as a PNG, while a run is recorded frame by
+//! frame in a [`Recording`] and written as an animated GIF or as a sequence of PNGs.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::geom::{vec2, Bounds, Vec2};
+use crate::gif::GifWriter;
+use crate::png;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// A `width`×`height` image of palette indices, row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// An image with `pixel` giving the palette index at each `(x, y)`.
+    pub fn new(width: usize, height: usize, mut pixel: impl FnMut(usize, usize) -> u8) -> Self {
+        let pixels = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| pixel(x, y)).collect();
+        Self { width, height, pixels }
+    }
+
+    /// An image of the cells of `bounds`, one pixel each, with `cell` giving the palette index of each
+    /// position.
+    pub fn render(bounds: Bounds<Vec2<i64>>, mut cell: impl FnMut(Vec2<i64>) -> u8) -> Self {
+        let size = bounds.size();
+        Self::new(size.x as usize, size.y as usize, |x, y| cell(bounds.min + vec2(x as i64, y as i64)))
+    }
+
+    /// The image with every pixel turned into a `scale`×`scale` square.
+    pub fn scale(&self, scale: usize) -> Self {
+        Self::new(self.width * scale, self.height * scale, |x, y| self.pixels[y / scale * self.width + x / scale])
+    }
+
+    /// The image centered in a `width`×`height` one filled with the colour 0.
+    pub fn pad(&self, width: usize, height: usize) -> Self {
+        let (dx, dy) = ((width - self.width) / 2, (height - self.height) / 2);
+        Self::new(width, height, |x, y| {
+            let (x, y) = (x.wrapping_sub(dx), y.wrapping_sub(dy));
+            if x < self.width && y < self.height { self.pixels[y * self.width + x] } else { 0 }
+        })
+    }
+}
+
+/// How a [`Recording`] is written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationFormat {
+    Gif,
+    /// One PNG per frame, numbered from 0.
+    Png,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub dir: PathBuf,
+    pub animation: AnimationFormat,
+    /// Hundredths of a second between the frames of GIFs.
+    pub delay: u16,
+}
+
+static OPTIONS: Mutex<Option<ExportOptions>> = Mutex::new(None);
+
+/// Enables the export with `options`, or disables it if `None` (the default).
+pub fn set_export(options: Option<ExportOptions>) {
+    *OPTIONS.lock().unwrap() = options;
+}
+
+pub fn enabled() -> bool {
+    OPTIONS.lock().unwrap().is_some()
+}
+
+fn options() -> Option<ExportOptions> {
+    OPTIONS.lock().unwrap().clone()
+}
+
+/// Writes `image` to `<name>.png` in the export directory, if enabled. Failures are reported without
+/// interrupting the day.
+pub fn save(name: &str, image: &Image, palette: &[Rgb]) {
+    if let Some(options) = options() {
+        report(write_png(&options.dir, &format!("{name}.png"), image, palette));
+    }
+}
+
+fn write_png(dir: &Path, file_name: &str, image: &Image, palette: &[Rgb]) -> io::Result<PathBuf> {
+    let path = dir.join(file_name);
+    write_file(&path, |w| png::write(w, image, palette))?;
+    Ok(path)
+}
+
+fn write_file(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut w = BufWriter::new(File::create(path)?);
+    write(&mut w)?;
+    w.flush()
+}
+
+fn report(result: io::Result<PathBuf>) {
+    match result {
+        Ok(path) => crate::info!("wrote {}", path.display()),
+        Err(err) => crate::error!("failed to export image: {err}"),
+    }
+}
+
+/// Frames of a run, written when [`finish`](Recording::finish)ed. Frames smaller than the largest one
+/// are centered in it.
+pub struct Recording {
+    name: String,
+    palette: &'static [Rgb],
+    scale: usize,
+    frames: Vec<Image>,
+}
+
+impl Recording {
+    /// A recording of `<name>.gif` (or `<name>-N.png`) with frames scaled by `scale`, `None` if the
+    /// export isn't enabled so that the frames aren't rendered for nothing.
+    pub fn new(name: &str, palette: &'static [Rgb], scale: usize) -> Option<Self> {
+        enabled().then(|| Self { name: name.into(), palette, scale, frames: Vec::new() })
+    }
+
+    pub fn add(&mut self, frame: Image) {
+        self.frames.push(frame);
+    }
+
+    pub fn finish(self) {
+        let Some(options) = options() else { return };
+        let width = self.frames.iter().map(|f| f.width).max().unwrap_or(0);
+        let height = self.frames.iter().map(|f| f.height).max().unwrap_or(0);
+        // frames are only scaled when written to keep the recording small
+        let mut frames = self.frames.iter().map(|f| f.pad(width, height).scale(self.scale));
+        match options.animation {
+            AnimationFormat::Gif => {
+                let path = options.dir.join(format!("{}.gif", self.name));
+                let result = write_file(&path, |w| {
+                    let mut gif = GifWriter::new(w, width * self.scale, height * self.scale, self.palette, options.delay)?;
+                    frames.try_for_each(|f| gif.frame(&f))?;
+                    gif.finish().map(drop)
+                });
+                report(result.map(|_| path));
+            },
+            AnimationFormat::Png => {
+                let digits = self.frames.len().saturating_sub(1).to_string().len();
+                for (i, frame) in frames.enumerate() {
+                    let file_name = format!("{}-{i:0digits$}.png", self.name);
+                    if let Err(err) = write_png(&options.dir, &file_name, &frame, self.palette) {
+                        return report(Err(err));
+                    }
+                }
+                let pattern = options.dir.join(format!("{}-*.png", self.name));
+                crate::info!("wrote {} frames to {}", self.frames.len(), pattern.display());
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image() {
+        let bounds = Bounds { min: vec2(-1, 0), max: vec2(0, 1) };
+        let image = Image::render(bounds, |p| (p.x + 1 + p.y * 2) as u8).scale(2);
+        assert_eq!((image.width, image.height), (4, 4));
+        assert_eq!(image.pixels, [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 3, 3, 2, 2, 3, 3]);
+
+        let image = Image::new(1, 2, |_, y| y as u8 + 1).pad(3, 4);
+        assert_eq!(image.pixels, [0, 0, 0, 0, 1, 0, 0, 2, 0, 0, 0, 0]);
+    }
+}
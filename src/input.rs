@@ -0,0 +1,108 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Directory searched for `inputN` files when neither `--data-dir` nor `AOC_DATA_DIR` are given.
+pub const DEFAULT_DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/data");
+
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    /// Read `<dir>/input<N>` for each day.
+    DataDir(PathBuf),
+    /// Read a specific file, only meaningful when running a single day.
+    File(PathBuf),
+    Stdin,
+    /// The example of each day's puzzle, built into the crate.
+    Example,
+}
+
+impl InputSource {
+    pub fn default_data_dir() -> Self {
+        match std::env::var_os("AOC_DATA_DIR") {
+            Some(dir) => InputSource::DataDir(dir.into()),
+            None => InputSource::DataDir(DEFAULT_DATA_DIR.into()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    pub day: u8,
+    pub path: Option<PathBuf>,
+    pub error: io::Error,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) if self.error.kind() == io::ErrorKind::NotFound =>
+                write!(f, "input for day {} not found at '{}' (use --input <FILE> or --data-dir <DIR>)", self.day, path.display()),
+            Some(path) => write!(f, "failed to read input for day {} from '{}': {}", self.day, path.display(), self.error),
+            None => write!(f, "failed to read input for day {} from stdin: {}", self.day, self.error),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Examples of the puzzles, also stored next to the inputs as `src/data/exampleN`.
+const EXAMPLES: [&str; 25] = [
+    include_str!("data/example1"),
+    include_str!("data/example2"),
+    include_str!("data/example3"),
+    include_str!("data/example4"),
+    include_str!("data/example5"),
+    include_str!("data/example6"),
+    include_str!("data/example7"),
+    include_str!("data/example8"),
+    include_str!("data/example9"),
+    include_str!("data/example10"),
+    include_str!("data/example11"),
+    include_str!("data/example12"),
+    include_str!("data/example13"),
+    include_str!("data/example14"),
+    include_str!("data/example15"),
+    include_str!("data/example16"),
+    include_str!("data/example17"),
+    include_str!("data/example18"),
+    include_str!("data/example19"),
+    include_str!("data/example20"),
+    include_str!("data/example21"),
+    include_str!("data/example22"),
+    include_str!("data/example23"),
+    include_str!("data/example24"),
+    include_str!("data/example25"),
+];
+
+/// Where the example of `day` is stored in the sources.
+pub fn example_file(day: u8) -> PathBuf {
+    Path::new(DEFAULT_DATA_DIR).join(format!("example{day}"))
+}
+
+pub fn example(day: u8) -> Option<&'static str> {
+    EXAMPLES.get(usize::from(day).checked_sub(1)?).copied()
+}
+
+pub fn data_file(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("input{day}"))
+}
+
+pub fn load(day: u8, source: &InputSource) -> Result<String, InputError> {
+    let read_file = |path: PathBuf| std::fs::read_to_string(&path)
+        .map_err(|error| InputError { day, path: Some(path), error });
+
+    match source {
+        InputSource::DataDir(dir) => read_file(data_file(dir, day)),
+        InputSource::File(path) => read_file(path.clone()),
+        InputSource::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)
+                .map_err(|error| InputError { day, path: None, error })?;
+            Ok(input)
+        },
+        InputSource::Example => match example(day) {
+            Some(example) => Ok(example.into()),
+            None => Err(InputError { day, path: Some(example_file(day)), error: io::ErrorKind::NotFound.into() }),
+        },
+    }
+}
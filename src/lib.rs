@@ -0,0 +1,97 @@
+//! Advent of Code 2022 solutions.
+//!
+//! Every day is a module exposing `parse`, which turns the puzzle input into the day's own
+//! representation, and `part1`/`part2`, which solve each half of the puzzle from it. The same
+//! functions are also available through the [`Solution`] implemented by each day (`day1::Day1`,
+//! `day2::Day2`, ...), which is what [`DAYS`] uses to run all of them the same way.
+
+pub mod answer;
+pub mod answers;
+pub mod bench;
+pub mod checked;
+pub mod combinator;
+pub mod cycle;
+pub mod gen;
+pub mod geom;
+pub mod gif;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod log;
+pub mod parallel;
+pub mod parser;
+pub mod png;
+pub mod profiler;
+pub mod report;
+pub mod runner;
+pub mod search;
+pub mod solution;
+pub mod visualize;
+
+pub use answer::Answer;
+pub use parser::ParseError;
+pub use solution::{DynSolution, Solution};
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+pub struct Day {
+    pub number: u8,
+    pub title: &'static str,
+    pub solution: &'static dyn DynSolution,
+}
+
+pub const DAYS: [Day; 25] = [
+    Day { number: 1, title: "Calorie Counting", solution: &day1::Day1 },
+    Day { number: 2, title: "Rock Paper Scissors", solution: &day2::Day2 },
+    Day { number: 3, title: "Rucksack Reorganization", solution: &day3::Day3 },
+    Day { number: 4, title: "Camp Cleanup", solution: &day4::Day4 },
+    Day { number: 5, title: "Supply Stacks", solution: &day5::Day5 },
+    Day { number: 6, title: "Tuning Trouble", solution: &day6::Day6 },
+    Day { number: 7, title: "No Space Left On Device", solution: &day7::Day7 },
+    Day { number: 8, title: "Treetop Tree House", solution: &day8::Day8 },
+    Day { number: 9, title: "Rope Bridge", solution: &day9::Day9 },
+    Day { number: 10, title: "Cathode-Ray Tube", solution: &day10::Day10 },
+    Day { number: 11, title: "Monkey in the Middle", solution: &day11::Day11 },
+    Day { number: 12, title: "Hill Climbing Algorithm", solution: &day12::Day12 },
+    Day { number: 13, title: "Distress Signal", solution: &day13::Day13 },
+    Day { number: 14, title: "Regolith Reservoir", solution: &day14::Day14 },
+    Day { number: 15, title: "Beacon Exclusion Zone", solution: &day15::Day15 },
+    Day { number: 16, title: "Proboscidea Volcanium", solution: &day16::Day16 },
+    Day { number: 17, title: "Pyroclastic Flow", solution: &day17::Day17 },
+    Day { number: 18, title: "Boiling Boulders", solution: &day18::Day18 },
+    Day { number: 19, title: "Not Enough Minerals", solution: &day19::Day19 },
+    Day { number: 20, title: "Grove Positioning System", solution: &day20::Day20 },
+    Day { number: 21, title: "Monkey Math", solution: &day21::Day21 },
+    Day { number: 22, title: "Monkey Map", solution: &day22::Day22 },
+    Day { number: 23, title: "Unstable Diffusion", solution: &day23::Day23 },
+    Day { number: 24, title: "Blizzard Basin", solution: &day24::Day24 },
+    Day { number: 25, title: "Full of Hot Air", solution: &day25::Day25 },
+];
+
+pub fn find_day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
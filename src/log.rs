@@ -0,0 +1,141 @@
+//! Leveled diagnostic output of the days (round tables, intermediate sizes, dumps of the state...)
+//! and of the runner, printed to stderr with [`error!`](crate::error!), [`warn!`](crate::warn!),
+//! [`info!`](crate::info!), [`debug!`](crate::debug!) and [`trace!`](crate::trace!).
+//!
+//! Only messages up to [`level`] are printed, [`Level::Info`] by default so that stdout is just the
+//! answers. The level is read from `$AOC_LOG` and then raised by `-v` and lowered by `-q`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Name of the environment variable holding the starting level, such as `AOC_LOG=debug`.
+pub const ENV_VAR: &str = "AOC_LOG";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Nothing is printed.
+    Off,
+    Error,
+    Warn,
+    /// Files written by the exports, progress of the runner.
+    Info,
+    /// Intermediate results of the days, a few lines each.
+    Debug,
+    /// Dumps of the whole state of the days, such as the tower of day 17 or the round tables of day 11.
+    Trace,
+}
+
+const LEVELS: [Level; 6] = [Level::Off, Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+impl Level {
+    /// Level `steps` more verbose than this one (or less verbose if negative), saturating at
+    /// [`Off`](Level::Off) and [`Trace`](Level::Trace).
+    pub fn offset(self, steps: i32) -> Level {
+        let i = (self as i32 + steps).clamp(0, LEVELS.len() as i32 - 1);
+        LEVELS[i as usize]
+    }
+
+    /// Level from `$AOC_LOG`, or [`Info`](Level::Info) if it isn't set.
+    pub fn from_env() -> Result<Level, String> {
+        match std::env::var(ENV_VAR) {
+            Ok(value) => value.parse().map_err(|err| format!("{err} in ${ENV_VAR}")),
+            Err(_) => Ok(Level::Info),
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Level, String> {
+        LEVELS.into_iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("invalid log level '{s}' (expected off, error, warn, info, debug or trace)"))
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Off => "off",
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        })
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    LEVELS[LEVEL.load(Ordering::Relaxed) as usize]
+}
+
+/// Lowers the level to `max` if it is more verbose, for the commands whose output the days' messages
+/// would get in the way of, like `bench`.
+pub fn limit_level(max: Level) {
+    set_level(level().min(max));
+}
+
+pub fn enabled(level: Level) -> bool {
+    level <= self::level()
+}
+
+/// Like `eprintln!`, but only prints (and evaluates its arguments) when `level` is [`enabled`].
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::log::enabled($level) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Error, "error: {}", format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Warn, "warning: {}", format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Info, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Debug, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Trace, $($arg)*) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!("debug".parse(), Ok(Level::Debug));
+        assert_eq!("WARN".parse(), Ok(Level::Warn));
+        assert!("verbose".parse::<Level>().is_err());
+
+        assert_eq!(Level::Info.offset(1), Level::Debug);
+        assert_eq!(Level::Info.offset(5), Level::Trace);
+        assert_eq!(Level::Info.offset(-2), Level::Error);
+        assert_eq!(Level::Error.offset(-3), Level::Off);
+    }
+}
@@ -0,0 +1,611 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use aoc_2022::answers::{self, Answers};
+use aoc_2022::bench::{self, Baseline, HumanDuration};
+use aoc_2022::gen;
+use aoc_2022::image::{self, AnimationFormat, ExportOptions};
+use aoc_2022::input::{self, InputSource};
+use aoc_2022::report::{self, DayReport, InputInfo};
+use aoc_2022::runner;
+use aoc_2022::log::{self, Level};
+use aoc_2022::{find_day, parallel, profiler, visualize, Answer, Day, ParseError, DAYS};
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: profiler::CountingAlloc = profiler::CountingAlloc;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Text,
+    /// One JSON object per day and per line, see [`report`].
+    Json,
+}
+
+struct RunOptions {
+    days: Vec<u8>,
+    source: InputSource,
+    format: Format,
+    /// Write the profiler spans as a Chrome trace to this file.
+    trace: Option<PathBuf>,
+    /// Write the profiler spans as folded stacks to this file.
+    folded: Option<PathBuf>,
+    /// Animate the simulations at this frame rate.
+    visualize: Option<u32>,
+    /// Write images of the simulations.
+    export: Option<ExportOptions>,
+    /// Run the days at the same time, each on its own thread.
+    parallel: bool,
+    threads: usize,
+}
+
+struct BenchOptions {
+    days: Vec<u8>,
+    source: InputSource,
+    warmup: usize,
+    iterations: usize,
+    baseline: PathBuf,
+    /// Overwrite the baseline with the new results.
+    save: bool,
+    threads: usize,
+}
+
+struct VerifyOptions {
+    days: Vec<u8>,
+    source: InputSource,
+    answers: PathBuf,
+    threads: usize,
+}
+
+struct GenOptions {
+    day: u8,
+    seed: u64,
+    size: Option<usize>,
+}
+
+enum Command {
+    Run(RunOptions),
+    Bench(BenchOptions),
+    Verify(VerifyOptions),
+    Gen(GenOptions),
+    List,
+    Help,
+}
+
+const USAGE: &str = "\
+Usage: aoc <command> [options]
+
+Commands:
+    run [DAYS]    Run the solutions for the given days (default: all)
+    bench [DAYS]  Time parsing and each part separately over many runs, comparing to the baseline
+    verify [DAYS] Check the answers of the given days (default: all) against the expected ones
+    gen DAY       Print a random input for the day
+    list          List the available days
+    help          Show this message
+
+Options:
+    -i, --input <FILE>    Read the puzzle input from FILE, or from stdin if FILE is '-' (single day only)
+    -d, --data-dir <DIR>  Read the puzzle inputs from DIR/inputN (default: $AOC_DATA_DIR or src/data)
+    -e, --example         Use the examples of the puzzles, built into the binary, instead of the inputs
+    -v, --verbose         Print more diagnostics of the days to stderr, repeat for more (default level: $AOC_LOG or info)
+    -q, --quiet           Print fewer diagnostics to stderr, repeat for fewer
+        --trace <FILE>    Write the profiled spans to FILE as Chrome trace-event JSON (chrome://tracing, Perfetto)
+        --folded <FILE>   Write the profiled spans to FILE as folded stacks for flamegraph tools
+        --format <FORMAT> Print the results of 'run' as 'text' (default) or as 'json', one object per day
+        --visualize       Animate the simulations of days 9, 14, 17, 23 and 24 in the terminal with 'run'
+        --fps <N>         Frame rate of the animations (default: 30)
+        --export <DIR>    Write images of days 14, 15 and 22 and animations of days 23 and 24 to DIR with 'run'
+        --animation <FMT> Write the animations as a 'gif' (default) or as 'png' frames
+        --parallel        Run the days of 'run' at the same time, then print their answers and times in a table
+        --threads <N>     Split the work of days 12, 15, 16 and 19 between N threads (default: 1)
+
+Bench options:
+    -n, --iterations <N>   Number of timed runs (default: 10)
+        --warmup <N>       Number of untimed runs before the timed ones (default: 3)
+        --baseline <FILE>  Compare against and save the results to FILE (default: target/bench-baseline.txt)
+        --no-save          Compare against the baseline without overwriting it
+
+Verify options:
+    -a, --answers <FILE>   Read the expected answers from FILE (default: answers.toml in the data dir)
+
+Gen options:
+        --seed <N>         Seed of the random input, the same seed giving the same input (default: 0)
+        --size <N>         Size of the input, in a unit depending on the day (default: close to the puzzle input)
+
+DAYS can be a single day (16), a range (1..=25, 1..5) or a comma-separated list of both (1,3,10..=12).";
+
+fn parse_day(s: &str) -> Result<u8, String> {
+    let n = s.trim().parse::<u8>().map_err(|_| format!("invalid day '{s}'"))?;
+    match find_day(n) {
+        Some(_) => Ok(n),
+        None => Err(format!("day {n} does not exist")),
+    }
+}
+
+fn parse_days(spec: &str) -> Result<Vec<u8>, String> {
+    let mut days = Vec::new();
+    for part in spec.split(',') {
+        let range = if let Some((start, end)) = part.split_once("..=") {
+            parse_day(start)?..=parse_day(end)?
+        } else if let Some((start, end)) = part.split_once("..") {
+            parse_day(start)?..=parse_day(end)?.saturating_sub(1)
+        } else {
+            days.push(parse_day(part)?);
+            continue;
+        };
+        if range.is_empty() {
+            return Err(format!("empty range of days '{part}'"));
+        }
+        days.extend(range);
+    }
+    Ok(days)
+}
+
+fn parse_count(arg: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("invalid value '{value}' for '{arg}'"))
+}
+
+fn parse_args(args: &[String]) -> Result<(Command, Level), String> {
+    let mut positional = Vec::new();
+    let mut verbosity = 0;
+    let mut source = None;
+    let mut example = false;
+    let mut trace = None;
+    let mut folded = None;
+    let mut warmup = 3;
+    let mut iterations = 10;
+    let mut baseline = None;
+    let mut save = true;
+    let mut answers = None;
+    let mut format = Format::Text;
+    let mut visualize = false;
+    let mut fps = visualize::DEFAULT_FRAME_RATE;
+    let mut export_dir: Option<PathBuf> = None;
+    let mut animation = AnimationFormat::Gif;
+    let mut parallel = false;
+    let mut threads = 1;
+    let mut seed = None;
+    let mut size = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for '{arg}'"));
+        match arg.as_str() {
+            "-i" | "--input" => source = Some(match value()?.as_str() {
+                "-" => InputSource::Stdin,
+                path => InputSource::File(path.into()),
+            }),
+            "-d" | "--data-dir" => source = Some(InputSource::DataDir(value()?.into())),
+            "-e" | "--example" => example = true,
+            "--trace" => trace = Some(value()?.into()),
+            "--folded" => folded = Some(value()?.into()),
+            "--format" => format = match value()?.as_str() {
+                "text" => Format::Text,
+                "json" => Format::Json,
+                other => return Err(format!("invalid format '{other}' (expected 'text' or 'json')")),
+            },
+            "--visualize" => visualize = true,
+            "--fps" => fps = match value()?.parse() {
+                Ok(fps) if fps > 0 => fps,
+                _ => return Err(format!("invalid value for '{arg}' (expected a positive number)")),
+            },
+            "--export" => export_dir = Some(value()?.into()),
+            "--animation" => animation = match value()?.as_str() {
+                "gif" => AnimationFormat::Gif,
+                "png" => AnimationFormat::Png,
+                other => return Err(format!("invalid animation format '{other}' (expected 'gif' or 'png')")),
+            },
+            "--parallel" => parallel = true,
+            "--threads" => threads = match value()?.parse() {
+                Ok(threads) if threads > 0 => threads,
+                _ => return Err(format!("invalid value for '{arg}' (expected a positive number)")),
+            },
+            "-n" | "--iterations" => iterations = parse_count(arg, value()?)?,
+            "--warmup" => warmup = parse_count(arg, value()?)?,
+            "--baseline" => baseline = Some(value()?.into()),
+            "--no-save" => save = false,
+            "-a" | "--answers" => answers = Some(value()?.into()),
+            "--seed" => seed = Some(value()?.parse().map_err(|_| format!("invalid value for '{arg}' (expected a number)"))?),
+            "--size" => size = Some(parse_count(arg, value()?)?),
+            "--verbose" => verbosity += 1,
+            "--quiet" => verbosity -= 1,
+            // -v, -vv, -qqq...
+            flag if flag.len() > 1 && flag[1..].bytes().all(|b| b == b'v') => verbosity += flag.len() as i32 - 1,
+            flag if flag.len() > 1 && flag[1..].bytes().all(|b| b == b'q') => verbosity -= flag.len() as i32 - 1,
+            "-h" | "--help" => return Ok((Command::Help, Level::Info)),
+            flag if flag.starts_with('-') => return Err(format!("unknown option '{flag}'")),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    if example {
+        if source.is_some() {
+            return Err("--example can't be used with --input or --data-dir".into());
+        }
+        source = Some(InputSource::Example);
+    }
+
+    let days_and_source = |cmd: &str, rest: &[&str]| -> Result<(Vec<u8>, InputSource), String> {
+        let days = match rest {
+            [] => DAYS.iter().map(|d| d.number).collect(),
+            [spec] => parse_days(spec)?,
+            _ => return Err(format!("too many arguments for '{cmd}'")),
+        };
+        if days.len() != 1 && matches!(source, Some(InputSource::File(_) | InputSource::Stdin)) {
+            return Err("--input can only be used when running a single day".into());
+        }
+        Ok((days, source.clone().unwrap_or_else(InputSource::default_data_dir)))
+    };
+
+    if format != Format::Text && !matches!(positional.first(), Some(&"run")) {
+        return Err("--format can only be used with 'run'".into());
+    }
+    if visualize && (format != Format::Text || !matches!(positional.first(), Some(&"run"))) {
+        return Err("--visualize can only be used with 'run' and the text format".into());
+    }
+    if export_dir.is_some() && !matches!(positional.first(), Some(&"run")) {
+        return Err("--export can only be used with 'run'".into());
+    }
+    if parallel && (format != Format::Text || visualize || trace.is_some() || folded.is_some()) {
+        return Err("--parallel can't be used with --format, --visualize, --trace or --folded".into());
+    }
+    if parallel && !matches!(positional.first(), Some(&"run")) {
+        return Err("--parallel can only be used with 'run'".into());
+    }
+    if (seed.is_some() || size.is_some()) && !matches!(positional.first(), Some(&"gen")) {
+        return Err("--seed and --size can only be used with 'gen'".into());
+    }
+
+    let level = Level::from_env()?.offset(verbosity);
+    let command = match positional.as_slice() {
+        [] | ["help"] => Ok(Command::Help),
+        ["list"] => Ok(Command::List),
+        ["run", rest @ ..] => {
+            let (days, source) = days_and_source("run", rest)?;
+            let visualize = visualize.then_some(fps);
+            // GIF delays are in hundredths of a second, and viewers slow down anything below 2
+            let delay = (100 / fps).max(2) as u16;
+            let export = export_dir.map(|dir| ExportOptions { dir, animation, delay });
+            Ok(Command::Run(RunOptions { days, source, format, trace, folded, visualize, export, parallel, threads }))
+        },
+        ["bench", rest @ ..] => {
+            let (days, source) = days_and_source("bench", rest)?;
+            Ok(Command::Bench(BenchOptions {
+                days,
+                source,
+                warmup,
+                iterations,
+                baseline: baseline.unwrap_or_else(|| bench::DEFAULT_BASELINE.into()),
+                save,
+                threads,
+            }))
+        },
+        ["verify", rest @ ..] => {
+            let (days, source) = days_and_source("verify", rest)?;
+            let answers = match (answers, &source) {
+                (Some(answers), _) => answers,
+                (None, InputSource::DataDir(dir)) => dir.join(answers::FILE_NAME),
+                (None, _) => return Err("--answers is required when verifying with --input or --example".into()),
+            };
+            Ok(Command::Verify(VerifyOptions { days, source, answers, threads }))
+        },
+        ["gen", day] => Ok(Command::Gen(GenOptions { day: parse_day(day)?, seed: seed.unwrap_or(0), size })),
+        ["gen", ..] => Err("'gen' needs a single day".into()),
+        [cmd, ..] => Err(format!("unknown command '{cmd}'")),
+    };
+    command.map(|command| (command, level))
+}
+
+fn print_answer(part: u8, answer: &Answer) {
+    match answer {
+        Answer::Screen(rows) => {
+            println!("[Part {part}] Result is:");
+            for row in rows {
+                println!("\t{row}");
+            }
+        },
+        answer => println!("[Part {part}] Result is {answer}"),
+    }
+}
+
+/// Runs both parts of the day, printing their answers as they are found in the text format.
+fn run_day(day: &Day, input: &str, source: &InputSource, format: Format) -> Result<[Answer; 2], ParseError> {
+    let _p = profiler::span(format!("day{}", day.number));
+
+    let parsed = {
+        let _p = profiler::span("parse");
+        day.solution.parse_from(input, source)?
+    };
+    let answer1 = {
+        let _p = profiler::span("part1");
+        day.solution.part1(&*parsed)
+    };
+    if format == Format::Text {
+        print_answer(1, &answer1);
+    }
+    let answer2 = {
+        let _p = profiler::span("part2");
+        day.solution.part2(&*parsed)
+    };
+    if format == Format::Text {
+        print_answer(2, &answer2);
+    }
+    Ok([answer1, answer2])
+}
+
+fn bench_day(day: &Day, input: &str, opts: &BenchOptions, baseline: &mut Baseline) -> Result<(), ParseError> {
+    let stats = bench::run(day, input, &opts.source, opts.warmup, opts.iterations)?;
+    let kind = bench::input_kind(&opts.source);
+
+    println!("{:8}{:>12}{:>12}{:>12}{:>12}{:>10}", "", "min", "median", "mean", "std dev", "change");
+    for (step, s) in bench::STEPS.iter().zip(&stats) {
+        let change = match baseline.get(day.number, kind, step) {
+            Some(base) => format!("{:+.1}%", bench::change(s, base)),
+            None => "-".into(),
+        };
+        println!(
+            "{step:8}{:>12}{:>12}{:>12}{:>12}{change:>10}",
+            HumanDuration(s.min),
+            HumanDuration(s.median),
+            HumanDuration(s.mean),
+            HumanDuration(s.std_dev),
+        );
+    }
+
+    baseline.insert(day.number, kind, &stats);
+    Ok(())
+}
+
+/// Result of checking one part in `verify`.
+enum Check {
+    Pass,
+    Fail(Answer),
+    /// No expected answer in the file.
+    Unknown,
+}
+
+fn verify_day(day: &Day, input: &str, source: &InputSource, expected: &Answers) -> Result<[Check; 2], ParseError> {
+    let parsed = day.solution.parse_from(input, source)?;
+    let answers = [day.solution.part1(&*parsed), day.solution.part2(&*parsed)];
+    Ok([1, 2].map(|part| {
+        let answer = answers[part as usize - 1].clone();
+        match expected.get(day.number, part) {
+            Some(expected) if *expected == answer => Check::Pass,
+            Some(_) => Check::Fail(answer),
+            None => Check::Unknown,
+        }
+    }))
+}
+
+/// Writes the profiler spans to `path` with `write`, returning whether it succeeded.
+fn write_profile(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> bool {
+    let result = File::create(path).and_then(|file| {
+        let mut w = BufWriter::new(file);
+        write(&mut w)?;
+        w.flush()
+    });
+    if let Err(err) = &result {
+        eprintln!("error: failed to write '{}': {err}", path.display());
+    }
+    result.is_ok()
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let cmd = match parse_args(&args) {
+        Ok((cmd, level)) => {
+            log::set_level(level);
+            cmd
+        },
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match cmd {
+        Command::Help => println!("{USAGE}"),
+        Command::List => {
+            for day in &DAYS {
+                println!("{:>2}  {}", day.number, day.title);
+            }
+        },
+        Command::Run(opts) => {
+            if opts.format == Format::Json {
+                // only the reports go to stdout
+                profiler::set_print_spans(false);
+            }
+            if let Some(fps) = opts.visualize {
+                // the frames are redrawn at the top of the terminal, other output would be overwritten
+                profiler::set_print_spans(false);
+                log::limit_level(Level::Info);
+                visualize::set_frame_rate(fps);
+            }
+            image::set_export(opts.export.clone());
+            parallel::set_threads(opts.threads);
+            profiler::set_record_events(opts.trace.is_some());
+
+            if opts.parallel {
+                // the output of the days would be interleaved, and their spans are kept by their threads
+                profiler::set_print_spans(false);
+                log::limit_level(Level::Info);
+                let start = Instant::now();
+                let days = opts.days.iter().map(|&n| find_day(n).unwrap()).collect::<Vec<_>>();
+                let runs = runner::run_parallel(&days, &opts.source);
+                runner::print_table(&runs, start.elapsed());
+                return if runs.iter().all(|r| r.result.is_ok()) { ExitCode::SUCCESS } else { ExitCode::FAILURE };
+            }
+
+            let mut failed = false;
+            for &number in &opts.days {
+                let day = find_day(number).unwrap();
+                match opts.format {
+                    Format::Text => {
+                        println!("--- Day {}: {} ---", day.number, day.title);
+                        let result = input::load(day.number, &opts.source)
+                            .map_err(|err| err.to_string())
+                            .and_then(|input| run_day(day, &input, &opts.source, opts.format).map_err(|err| err.to_string()));
+                        if let Err(err) = result {
+                            eprintln!("error: {err}");
+                            failed = true;
+                        }
+                        println!();
+                    },
+                    Format::Json => {
+                        let mut report = DayReport {
+                            day: day.number,
+                            title: day.title,
+                            input: None,
+                            answers: [Answer::None, Answer::None],
+                            error: None,
+                            spans: Vec::new(),
+                        };
+                        match input::load(day.number, &opts.source) {
+                            Ok(input) => {
+                                report.input = Some(InputInfo::new(day.number, &opts.source, &input));
+                                match run_day(day, &input, &opts.source, opts.format) {
+                                    Ok(answers) => report.answers = answers,
+                                    Err(err) => report.error = Some(err.to_string()),
+                                }
+                            },
+                            Err(err) => report.error = Some(err.to_string()),
+                        }
+                        report.spans = report::spans_under(&format!("day{}", day.number));
+                        failed |= report.error.is_some();
+
+                        let mut stdout = io::stdout().lock();
+                        if let Err(err) = report.write_json(&mut stdout).and_then(|_| stdout.flush()) {
+                            eprintln!("error: failed to write report: {err}");
+                            return ExitCode::FAILURE;
+                        }
+                    },
+                }
+            }
+            if opts.format == Format::Text {
+                profiler::print_summary();
+            }
+
+            if let Some(path) = &opts.trace {
+                failed |= !write_profile(path, profiler::write_chrome_trace);
+            }
+            if let Some(path) = &opts.folded {
+                failed |= !write_profile(path, profiler::write_folded);
+            }
+
+            if failed {
+                return ExitCode::FAILURE;
+            }
+        },
+        Command::Verify(opts) => {
+            profiler::set_print_spans(false);
+            log::limit_level(Level::Info);
+            parallel::set_threads(opts.threads);
+            let expected = match Answers::load(&opts.answers) {
+                Ok(expected) => expected,
+                Err(err) => {
+                    eprintln!("error: failed to read answers '{}': {err}", opts.answers.display());
+                    return ExitCode::FAILURE;
+                },
+            };
+
+            let mut rows = Vec::new();
+            for &number in &opts.days {
+                let day = find_day(number).unwrap();
+                let result = input::load(day.number, &opts.source)
+                    .map_err(|err| err.to_string())
+                    .and_then(|input| verify_day(day, &input, &opts.source, &expected).map_err(|err| err.to_string()));
+                rows.push((day, result));
+            }
+
+            let mut failed = false;
+            println!("Day  Part 1  Part 2");
+            for (day, result) in &rows {
+                match result {
+                    Ok(checks) => {
+                        let [c1, c2] = checks.each_ref().map(|c| match c {
+                            Check::Pass => "ok",
+                            Check::Fail(_) => "FAIL",
+                            Check::Unknown => "-",
+                        });
+                        println!("{:>3}  {c1:<6}  {c2}", day.number);
+                    },
+                    Err(_) => println!("{:>3}  ERROR   ERROR", day.number),
+                }
+            }
+
+            for (day, result) in &rows {
+                match result {
+                    Ok(checks) => {
+                        for (part, check) in (1..).zip(checks) {
+                            if let Check::Fail(answer) = check {
+                                let expected = expected.get(day.number, part).unwrap();
+                                eprintln!("day {} part {part}: expected {expected}, got {answer}", day.number);
+                                failed = true;
+                            }
+                        }
+                    },
+                    Err(err) => {
+                        eprintln!("day {}: {err}", day.number);
+                        failed = true;
+                    },
+                }
+            }
+
+            let checks = rows.iter().filter_map(|(_, r)| r.as_ref().ok()).flatten();
+            let passed = checks.clone().filter(|c| matches!(c, Check::Pass)).count();
+            let unknown = checks.filter(|c| matches!(c, Check::Unknown)).count();
+            println!("\n{passed} of {} parts passed, {unknown} without an expected answer", rows.len() * 2);
+            if failed {
+                return ExitCode::FAILURE;
+            }
+        },
+        Command::Gen(opts) => {
+            let generator = gen::generator_for(opts.day).unwrap();
+            let input = generator.generate(opts.seed, opts.size.unwrap_or(generator.default_size));
+            if let Err(err) = io::stdout().write_all(input.as_bytes()) {
+                eprintln!("error: failed to write the input: {err}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Command::Bench(opts) => {
+            profiler::set_print_spans(false);
+            log::limit_level(Level::Info);
+            parallel::set_threads(opts.threads);
+            let mut baseline = match Baseline::load(&opts.baseline) {
+                Ok(baseline) => baseline,
+                Err(err) => {
+                    eprintln!("error: failed to read baseline '{}': {err}", opts.baseline.display());
+                    return ExitCode::FAILURE;
+                },
+            };
+
+            let mut failed = false;
+            for &number in &opts.days {
+                let day = find_day(number).unwrap();
+                println!("--- Day {}: {} ---", day.number, day.title);
+                let result = input::load(day.number, &opts.source)
+                    .map_err(|err| err.to_string())
+                    .and_then(|input| bench_day(day, &input, &opts, &mut baseline).map_err(|err| err.to_string()));
+                if let Err(err) = result {
+                    eprintln!("error: {err}");
+                    failed = true;
+                }
+                println!();
+            }
+
+            if opts.save {
+                if let Err(err) = baseline.save(&opts.baseline) {
+                    eprintln!("error: failed to save baseline '{}': {err}", opts.baseline.display());
+                    failed = true;
+                }
+            }
+            if failed {
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+
+    ExitCode::SUCCESS
+}
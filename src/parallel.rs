@@ -0,0 +1,98 @@
+//! Splits the independent pieces of work of a day, such as the blueprints of day 19, between
+//! `--threads N` threads.
+//!
+//! With a single thread (the default) the work is done on the calling thread, as a plain iterator
+//! would. Results are the same whatever the number of threads, but the profiler spans entered by the
+//! pieces of work are only recorded with a single thread, the other threads keeping theirs.
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+static THREADS: AtomicUsize = AtomicUsize::new(1);
+
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads.max(1), Ordering::Relaxed);
+}
+
+pub fn threads() -> usize {
+    THREADS.load(Ordering::Relaxed)
+}
+
+/// `items.iter().map(f).collect()`, the items being handed out to the threads as they become free.
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    map_on(threads(), items, f)
+}
+
+/// `items.iter().find_map(f)`: the result of the first item, in order, for which `f` returns one. The
+/// items after one with a result aren't handed out anymore.
+pub fn find_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> Option<R> + Sync) -> Option<R> {
+    find_map_on(threads(), items, f)
+}
+
+fn map_on<T: Sync, R: Send>(threads: usize, items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    if threads.min(items.len()) <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results = work(threads.min(items.len()), || {
+        let mut results = Vec::new();
+        loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let Some(item) = items.get(i) else { return results };
+            results.push((i, f(item)));
+        }
+    });
+    results.sort_unstable_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+fn find_map_on<T: Sync, R: Send>(threads: usize, items: &[T], f: impl Fn(&T) -> Option<R> + Sync) -> Option<R> {
+    if threads.min(items.len()) <= 1 {
+        return items.iter().find_map(f);
+    }
+    let next = AtomicUsize::new(0);
+    let found = AtomicUsize::new(usize::MAX);
+    let results = work(threads.min(items.len()), || {
+        let mut results = Vec::new();
+        loop {
+            // the items before one with a result were all handed out already, and will be finished
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            if i >= items.len() || i > found.load(Ordering::Relaxed) {
+                return results;
+            }
+            if let Some(r) = f(&items[i]) {
+                found.fetch_min(i, Ordering::Relaxed);
+                results.push((i, r));
+            }
+        }
+    });
+    results.into_iter().min_by_key(|&(i, _)| i).map(|(_, r)| r)
+}
+
+/// Runs `worker` on `threads` threads and gathers the indexed results of all of them. A panic in a
+/// worker is passed on to the calling thread once the others are done.
+fn work<R: Send>(threads: usize, worker: impl Fn() -> Vec<(usize, R)> + Sync) -> Vec<(usize, R)> {
+    thread::scope(|scope| {
+        let workers = (0..threads).map(|_| scope.spawn(&worker)).collect::<Vec<_>>();
+        let results = workers.into_iter().map(|w| w.join()).collect::<Vec<_>>();
+        results.into_iter().flat_map(|r| r.unwrap_or_else(|payload| panic::resume_unwind(payload))).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel() {
+        let items = (0..1000u64).collect::<Vec<_>>();
+        let square = |&n: &u64| n * n;
+        assert_eq!(map_on(4, &items, square), items.iter().map(square).collect::<Vec<_>>());
+        assert_eq!(map_on(4, &[] as &[u64], square), []);
+
+        let multiple = |&n: &u64| (n > 10 && n % 7 == 0).then_some(n);
+        assert_eq!(find_map_on(4, &items, multiple), Some(14));
+        assert_eq!(find_map_on(3, &items, |_| None::<u64>), None);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Error produced when the puzzle input doesn't have the expected format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Day whose parser failed, filled in when parsing through [`crate::Solution`].
+    pub day: Option<u8>,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// What the parser was looking for, e.g. `"a number"` or `"\" -> \""`.
+    pub expected: String,
+    /// Input found at the error location, up to the end of the line.
+    pub found: String,
+}
+
+impl ParseError {
+    /// Creates an error located at the start of `at`, which must be a sub-slice of `input`
+    /// (as returned by `lines`, `split_once`, etc.).
+    pub fn at(input: &str, at: &str, expected: impl Into<String>) -> Self {
+        let offset = (at.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        debug_assert!(offset <= input.len(), "error location is not part of the input");
+        let offset = offset.min(input.len());
+
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let rest_of_line = input[offset..].lines().next().unwrap_or("");
+        Self {
+            day: None,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected: expected.into(),
+            found: rest_of_line.chars().take(24).collect(),
+        }
+    }
+
+    /// Creates an error located at the end of `at`, for when `at` ended before the parser found
+    /// what it expected.
+    pub fn after(input: &str, at: &str, expected: impl Into<String>) -> Self {
+        Self::at(input, &at[at.len()..], expected)
+    }
+
+    pub fn with_day(self, day: u8) -> Self {
+        Self { day: Some(day), ..self }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {day}, ")?;
+        }
+        write!(f, "line {}, column {}: expected {}, found ", self.line, self.column, self.expected)?;
+        if self.found.is_empty() {
+            write!(f, "end of line")
+        } else {
+            write!(f, "'{}'", self.found)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// `s.split_once(delimiter)`, failing if `delimiter` is missing.
+pub fn split_once<'a>(input: &str, s: &'a str, delimiter: &str) -> Result<(&'a str, &'a str), ParseError> {
+    s.split_once(delimiter).ok_or_else(|| ParseError::at(input, s, format!("'{delimiter}'")))
+}
+
+/// `s.strip_prefix(prefix)`, failing if `s` doesn't start with `prefix`.
+pub fn strip_prefix<'a>(input: &str, s: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+    s.strip_prefix(prefix).ok_or_else(|| ParseError::at(input, s, format!("'{prefix}'")))
+}
+
+/// Parses all of `s` as a number.
+pub fn number<T: FromStr>(input: &str, s: &str) -> Result<T, ParseError> {
+    s.parse().map_err(|_| ParseError::at(input, s, "a number"))
+}
+
+/// Returns the first byte of `s`, failing if `s` is empty.
+pub fn first_byte(input: &str, s: &str, expected: &str) -> Result<u8, ParseError> {
+    s.bytes().next().ok_or_else(|| ParseError::at(input, s, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let input = "1,2\n3;4\n";
+        let line = input.lines().nth(1).unwrap();
+        let err = split_once(input, line, ",").unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 1, "3;4"));
+
+        let err = number::<u8>(input, &line[1..]).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.with_day(5).to_string(), "day 5, line 2, column 2: expected a number, found ';4'");
+
+        let err = ParseError::after(input, line, "','");
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 4, ""));
+    }
+}
@@ -0,0 +1,99 @@
+//! Minimal PNG encoder for [`Image`]s, written as palette-based images.
+//!
+//! The pixel data is stored in uncompressed deflate blocks: the images are small enough that the
+//! size doesn't matter, and it keeps the encoder self-contained.
+
+use std::io::{self, Write};
+
+use crate::image::{Image, Rgb};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Largest length of a stored deflate block.
+const MAX_BLOCK: usize = 0xffff;
+
+pub fn write(w: &mut impl Write, image: &Image, palette: &[Rgb]) -> io::Result<()> {
+    assert!(!palette.is_empty() && palette.len() <= 256, "a PNG palette has 1 to 256 colours");
+    w.write_all(&SIGNATURE)?;
+
+    let mut ihdr = Vec::new();
+    ihdr.extend((image.width as u32).to_be_bytes());
+    ihdr.extend((image.height as u32).to_be_bytes());
+    // 8 bits per pixel, palette colour type, default compression, filter and no interlacing
+    ihdr.extend([8, 3, 0, 0, 0]);
+    write_chunk(w, b"IHDR", &ihdr)?;
+
+    let plte = palette.iter().flat_map(|c| [c.0, c.1, c.2]).collect::<Vec<_>>();
+    write_chunk(w, b"PLTE", &plte)?;
+
+    // every row starts with its filter type, 0 for none
+    let mut raw = Vec::with_capacity((image.width + 1) * image.height);
+    for row in image.pixels.chunks(image.width.max(1)).take(image.height) {
+        raw.push(0);
+        raw.extend(row);
+    }
+    write_chunk(w, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(w, b"IEND", &[])
+}
+
+fn write_chunk(w: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    w.write_all(&crc.to_be_bytes())
+}
+
+/// `data` as a zlib stream of stored (uncompressed) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate with a 32K window, no preset dictionary, and a check making the header a multiple of 31
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    !data.into_iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |crc, _| if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &d| {
+        let a = (a + d as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(zlib_stored(b"abc"), [0x78, 0x01, 1, 3, 0, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27]);
+        assert_eq!(zlib_stored(&vec![0; MAX_BLOCK + 1]).len(), 2 + 2 * 5 + MAX_BLOCK + 1 + 4);
+
+        let image = Image::new(2, 1, |x, _| x as u8);
+        let mut out = Vec::new();
+        write(&mut out, &image, &[Rgb(0, 0, 0), Rgb(255, 255, 255)]).unwrap();
+        assert_eq!(out[..8], SIGNATURE);
+        assert_eq!(out[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(out[16..29], [0, 0, 0, 2, 0, 0, 0, 1, 8, 3, 0, 0, 0]);
+        assert_eq!(out[out.len() - 12..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+    }
+}
@@ -1,21 +1,410 @@
-use std::time::Instant;
+//! Named, nested timing spans.
+//!
+//! Each thread records its spans in a tree: entering a span with the same name under the same parent
+//! again adds to that node instead of creating a new one, so repeated spans show up with a call count.
+//! With [`set_record_events`], every individual span is also kept so the timeline can be exported
+//! with [`write_chrome_trace`].
+//!
+//! When [`CountingAlloc`] is installed as the global allocator (the `alloc-stats` feature of the `aoc`
+//! binary), spans also record the allocations made while they were entered.
 
-pub struct Profiler {
-    start: Instant
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Allocations made by the current thread during a span.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocs: u64,
+    pub bytes: u64,
+    /// Highest amount of memory the span had allocated at once, on top of what was live when it started.
+    pub peak_bytes: u64,
+}
+
+impl AllocStats {
+    fn add(&mut self, other: AllocStats) {
+        self.allocs += other.allocs;
+        self.bytes += other.bytes;
+        self.peak_bytes = self.peak_bytes.max(other.peak_bytes);
+    }
+}
+
+struct Node {
+    name: Cow<'static, str>,
+    depth: usize,
+    calls: u64,
+    inclusive: Duration,
+    alloc: AllocStats,
+    children: Vec<usize>,
+}
+
+struct Event {
+    node: usize,
+    start: Instant,
+    duration: Duration,
+    alloc: AllocStats,
+}
+
+#[derive(Default)]
+struct Tree {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    /// Nodes of the spans currently entered, innermost last.
+    stack: Vec<usize>,
+    /// Every span that ended, only kept while [`RECORD_EVENTS`] is set.
+    events: Vec<Event>,
 }
 
-impl Profiler {
-    fn new() -> Self {
-        Self { start: Instant::now() }
+thread_local! {
+    static TREE: RefCell<Tree> = RefCell::default();
+}
+
+/// Time origin of the exported timelines, set when the first span starts.
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+static PRINT_SPANS: AtomicBool = AtomicBool::new(true);
+
+/// Sets whether each span prints its time when it ends (enabled by default).
+pub fn set_print_spans(enabled: bool) {
+    PRINT_SPANS.store(enabled, Ordering::Relaxed);
+}
+
+static RECORD_EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether every span is kept for [`write_chrome_trace`] (disabled by default), rather than only
+/// being added to the totals of its node, which take the same memory however many times it's entered.
+pub fn set_record_events(enabled: bool) {
+    RECORD_EVENTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Global allocator that counts the allocations of each thread, for the allocation columns of the
+/// profiler. Install it with `#[global_allocator]`.
+pub struct CountingAlloc;
+
+struct AllocCounters {
+    allocs: Cell<u64>,
+    bytes: Cell<u64>,
+    /// Can go negative when a thread frees memory allocated by another one.
+    live: Cell<i64>,
+    peak: Cell<i64>,
+}
+
+#[derive(Copy, Clone)]
+struct AllocSnapshot {
+    allocs: u64,
+    bytes: u64,
+    live: i64,
+    peak: i64,
+}
+
+thread_local! {
+    static ALLOC_COUNTERS: AllocCounters = const {
+        AllocCounters { allocs: Cell::new(0), bytes: Cell::new(0), live: Cell::new(0), peak: Cell::new(0) }
+    };
+}
+
+static ALLOC_TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// Whether [`CountingAlloc`] is the global allocator, i.e. whether spans have allocation stats.
+pub fn alloc_tracking() -> bool {
+    ALLOC_TRACKING.load(Ordering::Relaxed)
+}
+
+fn record_alloc(size: usize) {
+    if !ALLOC_TRACKING.load(Ordering::Relaxed) {
+        ALLOC_TRACKING.store(true, Ordering::Relaxed);
+    }
+    // `try_with` because the allocator can still be called while the thread-locals are being destroyed
+    let _ = ALLOC_COUNTERS.try_with(|c| {
+        c.allocs.set(c.allocs.get() + 1);
+        c.bytes.set(c.bytes.get() + size as u64);
+        c.live.set(c.live.get() + size as i64);
+        c.peak.set(c.peak.get().max(c.live.get()));
+    });
+}
+
+fn record_dealloc(size: usize) {
+    let _ = ALLOC_COUNTERS.try_with(|c| c.live.set(c.live.get() - size as i64));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
     }
 }
 
-impl Drop for Profiler {
+fn alloc_snapshot() -> AllocSnapshot {
+    ALLOC_COUNTERS.with(|c| AllocSnapshot { allocs: c.allocs.get(), bytes: c.bytes.get(), live: c.live.get(), peak: c.peak.get() })
+}
+
+/// Guard returned by [`span`], the span ends when it is dropped.
+pub struct Span {
+    node: usize,
+    start: Instant,
+    alloc_start: AllocSnapshot,
+    // spans are recorded in a thread-local tree, so they must end on the thread they started on
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for Span {
     fn drop(&mut self) {
-        println!("Took {} μs", self.start.elapsed().as_micros())
+        let elapsed = self.start.elapsed();
+        let alloc_end = alloc_snapshot();
+        let alloc = AllocStats {
+            allocs: alloc_end.allocs - self.alloc_start.allocs,
+            bytes: alloc_end.bytes - self.alloc_start.bytes,
+            peak_bytes: (alloc_end.peak - self.alloc_start.live).max(0) as u64,
+        };
+        // the peak of the enclosing span is the highest of the peak before this span and the one during it
+        ALLOC_COUNTERS.with(|c| c.peak.set(self.alloc_start.peak.max(alloc_end.peak)));
+
+        TREE.with_borrow_mut(|tree| {
+            debug_assert_eq!(tree.stack.last(), Some(&self.node), "spans must end in reverse order");
+            tree.stack.pop();
+
+            if RECORD_EVENTS.load(Ordering::Relaxed) {
+                tree.events.push(Event { node: self.node, start: self.start, duration: elapsed, alloc });
+            }
+            let node = &mut tree.nodes[self.node];
+            node.calls += 1;
+            node.inclusive += elapsed;
+            node.alloc.add(alloc);
+            if PRINT_SPANS.load(Ordering::Relaxed) {
+                println!("{:indent$}{} took {} μs", "", node.name, elapsed.as_micros(), indent = node.depth * 2);
+            }
+        });
+    }
+}
+
+/// Starts a span named `name`, nested in the innermost span currently entered on this thread.
+pub fn span(name: impl Into<Cow<'static, str>>) -> Span {
+    EPOCH.get_or_init(Instant::now);
+    let name = name.into();
+    let node = TREE.with_borrow_mut(|tree| {
+        let parent = tree.stack.last().copied();
+        let siblings = match parent {
+            Some(parent) => &tree.nodes[parent].children,
+            None => &tree.roots,
+        };
+
+        let node = match siblings.iter().copied().find(|&i| tree.nodes[i].name == name) {
+            Some(node) => node,
+            None => {
+                let node = tree.nodes.len();
+                tree.nodes.push(Node {
+                    name,
+                    depth: tree.stack.len(),
+                    calls: 0,
+                    inclusive: Duration::ZERO,
+                    alloc: AllocStats::default(),
+                    children: Vec::new(),
+                });
+                match parent {
+                    Some(parent) => tree.nodes[parent].children.push(node),
+                    None => tree.roots.push(node),
+                }
+                node
+            },
+        };
+        tree.stack.push(node);
+        node
+    });
+
+    let alloc_start = alloc_snapshot();
+    ALLOC_COUNTERS.with(|c| c.peak.set(alloc_start.live));
+    Span { node, start: Instant::now(), alloc_start, _not_send: PhantomData }
+}
+
+/// Totals of a span recorded on the current thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanStats {
+    pub name: String,
+    /// Nesting level, 0 for spans entered outside any other span.
+    pub depth: usize,
+    pub calls: u64,
+    /// Time spent in the span, including its child spans.
+    pub inclusive: Duration,
+    /// Time spent in the span but not in any of its child spans.
+    pub exclusive: Duration,
+    /// Allocations made in the span and its child spans, all zero unless [`alloc_tracking`].
+    pub alloc: AllocStats,
+}
+
+/// Returns the finished spans of the current thread, each followed by its children (depth-first).
+pub fn summary() -> Vec<SpanStats> {
+    fn visit(tree: &Tree, node: usize, stats: &mut Vec<SpanStats>) {
+        let n = &tree.nodes[node];
+        let children_time = n.children.iter().map(|&c| tree.nodes[c].inclusive).sum::<Duration>();
+        stats.push(SpanStats {
+            name: n.name.to_string(),
+            depth: n.depth,
+            calls: n.calls,
+            inclusive: n.inclusive,
+            exclusive: n.inclusive.saturating_sub(children_time),
+            alloc: n.alloc,
+        });
+        for &child in &n.children {
+            visit(tree, child, stats);
+        }
     }
+
+    TREE.with_borrow(|tree| {
+        let mut stats = Vec::new();
+        for &root in &tree.roots {
+            visit(tree, root, &mut stats);
+        }
+        stats
+    })
 }
 
-pub fn profile() -> Profiler {
-    Profiler::new()
-}
\ No newline at end of file
+/// Prints the [`summary`] as a table of inclusive and exclusive times.
+pub fn print_summary() {
+    let stats = summary();
+    if stats.is_empty() {
+        return;
+    }
+
+    let name_width = stats.iter().map(|s| s.depth * 2 + s.name.chars().count()).max().unwrap_or(0).max(4);
+    print!("{:name_width$}  {:>8}  {:>16}  {:>16}", "Span", "Calls", "Inclusive (μs)", "Exclusive (μs)");
+    if alloc_tracking() {
+        print!("  {:>12}  {:>16}  {:>16}", "Allocs", "Allocated (B)", "Peak (B)");
+    }
+    println!();
+    for s in &stats {
+        let name = format!("{:indent$}{}", "", s.name, indent = s.depth * 2);
+        print!("{name:name_width$}  {:>8}  {:>16}  {:>16}", s.calls, s.inclusive.as_micros(), s.exclusive.as_micros());
+        if alloc_tracking() {
+            print!("  {:>12}  {:>16}  {:>16}", s.alloc.allocs, s.alloc.bytes, s.alloc.peak_bytes);
+        }
+        println!();
+    }
+}
+
+/// Writes the finished spans of the current thread in the Chrome trace-event JSON format, which can be
+/// opened in `chrome://tracing` or <https://ui.perfetto.dev>. Only the spans that ended while
+/// [`set_record_events`] was enabled are written.
+pub fn write_chrome_trace(w: &mut impl Write) -> io::Result<()> {
+    let epoch = *EPOCH.get_or_init(Instant::now);
+    TREE.with_borrow(|tree| {
+        writeln!(w, "{{\"traceEvents\":[")?;
+        for (i, e) in tree.events.iter().enumerate() {
+            let separator = if i + 1 < tree.events.len() { "," } else { "" };
+            let args = if alloc_tracking() {
+                format!(",\"args\":{{\"allocs\":{},\"bytes\":{},\"peak_bytes\":{}}}", e.alloc.allocs, e.alloc.bytes, e.alloc.peak_bytes)
+            } else {
+                String::new()
+            };
+            writeln!(
+                w,
+                "{{\"name\":\"{}\",\"cat\":\"aoc\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":1{args}}}{separator}",
+                escape_json(&tree.nodes[e.node].name),
+                e.start.saturating_duration_since(epoch).as_secs_f64() * 1e6,
+                e.duration.as_secs_f64() * 1e6,
+            )?;
+        }
+        writeln!(w, "],\"displayTimeUnit\":\"ms\"}}")
+    })
+}
+
+/// Writes the finished spans of the current thread as folded stacks (`day19;part1;blueprint 111051`),
+/// weighted by exclusive time in microseconds, the input format of `flamegraph.pl` and `inferno`.
+pub fn write_folded(w: &mut impl Write) -> io::Result<()> {
+    let mut stack = Vec::<String>::new();
+    for s in summary() {
+        stack.truncate(s.depth);
+        stack.push(s.name.replace([';', ' '], "_"));
+        if s.exclusive.as_micros() > 0 {
+            writeln!(w, "{} {}", stack.join(";"), s.exclusive.as_micros())?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nesting() {
+        {
+            let _outer = span("outer");
+            for _ in 0..3 {
+                let _inner = span("inner");
+            }
+            let _other = span(format!("other{}", 1));
+        }
+        let _outer = span("outer");
+        drop(_outer);
+
+        let stats = summary();
+        let shape = stats.iter().map(|s| (s.name.as_str(), s.depth, s.calls)).collect::<Vec<_>>();
+        assert_eq!(shape, [("outer", 0, 2), ("inner", 1, 3), ("other1", 1, 1)]);
+        assert!(stats[0].exclusive <= stats[0].inclusive);
+        assert_eq!(stats[0].inclusive - stats[0].exclusive, stats[1].inclusive + stats[2].inclusive);
+    }
+
+    #[test]
+    fn test_export() {
+        set_record_events(true);
+        {
+            let _day = span("day \"1\"");
+            let _part = span("part1");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut trace = Vec::new();
+        write_chrome_trace(&mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.starts_with("{\"traceEvents\":[\n{\"name\":\"part1\""));
+        assert!(trace.contains("{\"name\":\"day \\\"1\\\"\""));
+
+        let mut folded = Vec::new();
+        write_folded(&mut folded).unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded.lines().any(|l| l.starts_with("day_\"1\";part1 ")));
+    }
+}
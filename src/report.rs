@@ -0,0 +1,144 @@
+//! Machine-readable report of a run, written as one JSON object per day and per line:
+//!
+//! ```json
+//! {"day":1,"title":"Calorie Counting","input":{"source":"src/data/input1","bytes":10470,"lines":2255},"part1":24000,"part2":45000,"error":null,"spans":[{"name":"day1","depth":0,"calls":1,"inclusive_ns":91000,"exclusive_ns":2000},...]}
+//! ```
+//!
+//! Answers are numbers, strings, arrays of strings for screens, or `null` when a part has no answer.
+//! Spans are the [`profiler`](crate::profiler) spans recorded under the day, with `allocs`, `bytes` and
+//! `peak_bytes` added when allocations are tracked.
+
+use std::io::{self, Write};
+
+use crate::input::{self, InputSource};
+use crate::profiler::{self, escape_json, SpanStats};
+use crate::Answer;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputInfo {
+    /// Path of the input file (of the example with `--example`), or `-` for stdin.
+    pub source: String,
+    pub bytes: usize,
+    pub lines: usize,
+}
+
+impl InputInfo {
+    pub fn new(day: u8, source: &InputSource, input: &str) -> Self {
+        let source = match source {
+            InputSource::DataDir(dir) => input::data_file(dir, day).display().to_string(),
+            InputSource::File(path) => path.display().to_string(),
+            InputSource::Stdin => "-".into(),
+            InputSource::Example => input::example_file(day).display().to_string(),
+        };
+        Self { source, bytes: input.len(), lines: input.lines().count() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DayReport {
+    pub day: u8,
+    pub title: &'static str,
+    /// `None` if the input couldn't be read.
+    pub input: Option<InputInfo>,
+    pub answers: [Answer; 2],
+    pub error: Option<String>,
+    pub spans: Vec<SpanStats>,
+}
+
+impl DayReport {
+    pub fn write_json(&self, w: &mut impl Write) -> io::Result<()> {
+        write!(w, "{{\"day\":{},\"title\":\"{}\",\"input\":", self.day, escape_json(self.title))?;
+        match &self.input {
+            Some(i) => write!(w, "{{\"source\":\"{}\",\"bytes\":{},\"lines\":{}}}", escape_json(&i.source), i.bytes, i.lines)?,
+            None => write!(w, "null")?,
+        }
+        for (part, answer) in (1..).zip(&self.answers) {
+            write!(w, ",\"part{part}\":{}", answer_json(answer))?;
+        }
+        match &self.error {
+            Some(err) => write!(w, ",\"error\":\"{}\"", escape_json(err))?,
+            None => write!(w, ",\"error\":null")?,
+        }
+
+        write!(w, ",\"spans\":[")?;
+        for (i, s) in self.spans.iter().enumerate() {
+            write!(
+                w,
+                "{}{{\"name\":\"{}\",\"depth\":{},\"calls\":{},\"inclusive_ns\":{},\"exclusive_ns\":{}",
+                if i > 0 { "," } else { "" },
+                escape_json(&s.name),
+                s.depth,
+                s.calls,
+                s.inclusive.as_nanos(),
+                s.exclusive.as_nanos(),
+            )?;
+            if profiler::alloc_tracking() {
+                write!(w, ",\"allocs\":{},\"bytes\":{},\"peak_bytes\":{}", s.alloc.allocs, s.alloc.bytes, s.alloc.peak_bytes)?;
+            }
+            write!(w, "}}")?;
+        }
+        writeln!(w, "]}}")
+    }
+}
+
+fn answer_json(answer: &Answer) -> String {
+    match answer {
+        Answer::Int(n) => n.to_string(),
+        Answer::Str(s) => format!("\"{}\"", escape_json(s)),
+        Answer::Screen(rows) => {
+            let rows = rows.iter().map(|r| format!("\"{}\"", escape_json(r))).collect::<Vec<_>>();
+            format!("[{}]", rows.join(","))
+        },
+        Answer::None => "null".into(),
+    }
+}
+
+/// The spans of the [`profiler::summary`] recorded under the top-level span `root`, including it.
+pub fn spans_under(root: &str) -> Vec<SpanStats> {
+    profiler::summary()
+        .into_iter()
+        .skip_while(|s| s.depth != 0 || s.name != root)
+        .enumerate()
+        .take_while(|(i, s)| *i == 0 || s.depth > 0)
+        .map(|(_, s)| s)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_json() {
+        let report = DayReport {
+            day: 10,
+            title: "Cathode-Ray \"Tube\"",
+            input: Some(InputInfo::new(10, &InputSource::Stdin, "noop\naddx 3\n")),
+            answers: [Answer::Int(-13140), Answer::Screen(vec!["#.".into(), ".#".into()])],
+            error: None,
+            spans: Vec::new(),
+        };
+        let mut out = Vec::new();
+        report.write_json(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"day":10,"title":"Cathode-Ray \"Tube\"","input":{"source":"-","bytes":12,"lines":2},"#,
+                r##""part1":-13140,"part2":["#.",".#"],"error":null,"spans":[]}"##,
+                "\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_spans_under() {
+        profiler::set_print_spans(false);
+        for root in ["day1", "day2"] {
+            let _p = profiler::span(root);
+            let _p = profiler::span("parse");
+        }
+        let names = spans_under("day1").into_iter().map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(names, ["day1", "parse"]);
+        assert!(spans_under("day3").is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+//! Runs several days at once, each on its own thread, for `aoc run --parallel`.
+//!
+//! A day that fails, even by panicking, only fails its own row of the table: the other days keep
+//! running and their answers are still printed.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bench::HumanDuration;
+use crate::input::{self, InputSource};
+use crate::{Answer, Day};
+
+/// Outcome of a day run on its own thread.
+pub struct DayRun {
+    pub day: &'static Day,
+    /// Answers of both parts, or why the day failed: an unreadable input, a parse error or a panic.
+    pub result: Result<[Answer; 2], String>,
+    pub wall: Duration,
+    /// Time the day's thread spent running, `None` where it can't be measured (outside Linux).
+    pub cpu: Option<Duration>,
+}
+
+/// Stack size of the days' threads, that of the main thread on Linux, which they get when run one after
+/// the other, rather than the 2 MiB threads get by default: some of the days recurse deeply.
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Runs `days` at the same time, each on a thread of its own, and returns their outcomes in the same
+/// order once all of them finished.
+pub fn run_parallel(days: &[&'static Day], source: &InputSource) -> Vec<DayRun> {
+    thread::scope(|scope| {
+        let handles = days
+            .iter()
+            .map(|&day| {
+                thread::Builder::new()
+                    .name(format!("day{}", day.number))
+                    .stack_size(STACK_SIZE)
+                    .spawn_scoped(scope, move || run_day(day, source))
+                    .expect("failed to spawn a thread")
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().expect("panics are caught by run_day")).collect()
+    })
+}
+
+fn run_day(day: &'static Day, source: &InputSource) -> DayRun {
+    let start = Instant::now();
+    let cpu_start = thread_cpu_time();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let input = input::load(day.number, source).map_err(|err| err.to_string())?;
+        let parsed = day.solution.parse_from(&input, source).map_err(|err| err.to_string())?;
+        Ok([day.solution.part1(&*parsed), day.solution.part2(&*parsed)])
+    }));
+    // the panic itself was already reported on stderr by the panic hook, with its location
+    let result = result.unwrap_or_else(|payload| Err(format!("panicked: {}", panic_message(&*payload))));
+    let cpu = cpu_start.zip(thread_cpu_time()).map(|(start, end)| end.saturating_sub(start));
+    DayRun { day, result, wall: start.elapsed(), cpu }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+        (Some(s), _) => s,
+        (_, Some(s)) => s,
+        _ => "unknown panic",
+    }
+}
+
+/// CPU time used by the current thread so far, read with `clock_gettime`, whose declaration is only
+/// spelled out for Linux.
+#[cfg(target_os = "linux")]
+fn thread_cpu_time() -> Option<Duration> {
+    use std::os::raw::{c_int, c_long};
+
+    #[repr(C)]
+    struct Timespec {
+        tv_sec: c_long,
+        tv_nsec: c_long,
+    }
+
+    const CLOCK_THREAD_CPUTIME_ID: c_int = 3;
+    extern "C" {
+        fn clock_gettime(clock: c_int, time: *mut Timespec) -> c_int;
+    }
+
+    let mut time = Timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `time` is a valid timespec for the duration of the call
+    let result = unsafe { clock_gettime(CLOCK_THREAD_CPUTIME_ID, &mut time) };
+    (result == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// Prints the answers and times of every day as a table, then the errors of the days that failed and
+/// the answers that are screens to read.
+pub fn print_table(runs: &[DayRun], total: Duration) {
+    let cell = |answer: &Answer| match answer {
+        Answer::Screen(_) => "(see below)".to_string(),
+        answer => answer.to_string(),
+    };
+    let rows = runs
+        .iter()
+        .map(|run| match &run.result {
+            Ok([part1, part2]) => [cell(part1), cell(part2)],
+            Err(_) => ["failed (see below)".into(), String::new()],
+        })
+        .collect::<Vec<_>>();
+    let width = |part: usize| rows.iter().map(|r| r[part].chars().count()).max().unwrap_or(0).max(6);
+    let (w1, w2) = (width(0), width(1));
+
+    println!("{:>3}  {:w1$}  {:w2$}  {:>10}  {:>10}", "Day", "Part 1", "Part 2", "Wall", "CPU");
+    for (run, [part1, part2]) in runs.iter().zip(&rows) {
+        let cpu = run.cpu.map_or("n/a".to_string(), |cpu| HumanDuration(cpu).to_string());
+        println!("{:>3}  {part1:w1$}  {part2:w2$}  {:>10}  {cpu:>10}", run.day.number, HumanDuration(run.wall).to_string());
+    }
+    println!("\nTotal wall time: {}", HumanDuration(total));
+
+    for run in runs {
+        if let Err(err) = &run.result {
+            println!("\nDay {} failed: {err}", run.day.number);
+        }
+        for (part, answer) in run.result.iter().flatten().enumerate() {
+            if let Answer::Screen(rows) = answer {
+                println!("\nDay {} part {}:", run.day.number, part + 1);
+                for row in rows {
+                    println!("\t{row}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseError, Solution};
+
+    struct Panicking;
+
+    impl Solution for Panicking {
+        const DAY: u8 = 2;
+        type Input = ();
+
+        fn parse(_: &str) -> Result<Self::Input, ParseError> {
+            Ok(())
+        }
+
+        fn part1(_: &Self::Input) -> Answer {
+            panic!("no answer")
+        }
+
+        fn part2(_: &Self::Input) -> Answer {
+            Answer::None
+        }
+    }
+
+    static PANICKING: Day = Day { number: 2, title: "Panicking", solution: &Panicking };
+
+    #[test]
+    fn test_run_parallel() {
+        let dir = std::env::temp_dir().join(format!("aoc-runner-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("input1"), "1\n2\n\n4\n").unwrap();
+        std::fs::write(dir.join("input2"), "").unwrap();
+        let missing = crate::find_day(3).unwrap();
+
+        let runs = run_parallel(&[crate::find_day(1).unwrap(), &PANICKING, missing], &InputSource::DataDir(dir.clone()));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(runs.iter().map(|r| r.day.number).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(runs[0].result, Ok([Answer::Int(4), Answer::Int(7)]));
+        assert_eq!(runs[1].result, Err("panicked: no answer".into()));
+        assert!(runs[2].result.is_err());
+    }
+}
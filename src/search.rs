@@ -0,0 +1,244 @@
+//! Graph searches over a state space described by a `successors` closure: [`bfs`] for unweighted
+//! edges, [`dijkstra`] and [`astar`] for weighted ones.
+//!
+//! The states reached are kept with their parent, so the path to the goal is reconstructed at the
+//! end instead of being carried along by every state. [`bfs_cost`] only keeps which states were
+//! reached, for callers that don't need the path.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Shortest path found by a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    /// States from the start to the goal, both included.
+    pub states: Vec<S>,
+    /// Sum of the costs of the edges, the number of steps for [`bfs`].
+    pub cost: C,
+}
+
+impl<S, C> Path<S, C> {
+    pub fn goal(&self) -> &S {
+        self.states.last().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search<S, C> {
+    /// `None` if no goal is reachable from the start.
+    pub path: Option<Path<S, C>>,
+    /// Number of states expanded, i.e. whose successors were generated.
+    pub visited: usize,
+}
+
+struct Node<S, C> {
+    state: S,
+    parent: Option<usize>,
+    cost: C,
+}
+
+/// States reached so far, indexed to link them to their parent.
+struct Nodes<S, C> {
+    nodes: Vec<Node<S, C>>,
+    index: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy + Ord> Nodes<S, C> {
+    fn new(start: S, cost: C) -> Self {
+        Self { nodes: vec![Node { state: start.clone(), parent: None, cost }], index: HashMap::from([(start, 0)]) }
+    }
+
+    /// Records that `state` is reached from `parent` with `cost`, returning its index if it is new or
+    /// cheaper than before.
+    fn reach(&mut self, state: S, parent: usize, cost: C) -> Option<usize> {
+        match self.index.entry(state) {
+            Entry::Occupied(e) => {
+                let node = &mut self.nodes[*e.get()];
+                if cost >= node.cost {
+                    return None;
+                }
+                node.parent = Some(parent);
+                node.cost = cost;
+                Some(*e.get())
+            },
+            Entry::Vacant(e) => {
+                self.nodes.push(Node { state: e.key().clone(), parent: Some(parent), cost });
+                Some(*e.insert(self.nodes.len() - 1))
+            },
+        }
+    }
+
+    fn path(&self, goal: usize) -> Path<S, C> {
+        let mut states = Vec::new();
+        let mut i = Some(goal);
+        while let Some(n) = i {
+            states.push(self.nodes[n].state.clone());
+            i = self.nodes[n].parent;
+        }
+        states.reverse();
+        Path { states, cost: self.nodes[goal].cost }
+    }
+}
+
+/// Breadth-first search from `start` to the first state matching `is_goal`, each edge costing one step.
+pub fn bfs<S, I>(start: S, mut successors: impl FnMut(&S) -> I, mut is_goal: impl FnMut(&S) -> bool) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut nodes = Nodes::new(start, 0);
+    let mut queue = VecDeque::from([0]);
+    let mut visited = 0;
+    while let Some(i) = queue.pop_front() {
+        if is_goal(&nodes.nodes[i].state) {
+            return Search { path: Some(nodes.path(i)), visited };
+        }
+        visited += 1;
+        let cost = nodes.nodes[i].cost + 1;
+        for next in successors(&nodes.nodes[i].state) {
+            // the first time a state is reached is the shortest, later ones are never cheaper
+            if let Some(n) = nodes.reach(next, i, cost) {
+                queue.push_back(n);
+            }
+        }
+    }
+    Search { path: None, visited }
+}
+
+/// Number of steps of the shortest path found by [`bfs_cost`], without the path itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostSearch {
+    /// `None` if no goal is reachable from the start.
+    pub cost: Option<usize>,
+    /// Number of states expanded, i.e. whose successors were generated.
+    pub visited: usize,
+}
+
+/// Like [`bfs`], only keeping the states reached in a set instead of linking them to their parent.
+pub fn bfs_cost<S, I>(start: S, successors: impl FnMut(&S) -> I, is_goal: impl FnMut(&S) -> bool) -> CostSearch
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut reached = HashSet::new();
+    bfs_cost_by(start, successors, is_goal, |s| reached.insert(s.clone()))
+}
+
+/// Like [`bfs_cost`], with `first_reach` recording that a state is reached and telling whether it is
+/// the first time, such as by setting a flag in a grid.
+pub fn bfs_cost_by<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+    mut first_reach: impl FnMut(&S) -> bool,
+) -> CostSearch
+where
+    I: IntoIterator<Item = S>,
+{
+    first_reach(&start);
+    let mut queue = VecDeque::from([(start, 0)]);
+    let mut visited = 0;
+    while let Some((state, cost)) = queue.pop_front() {
+        if is_goal(&state) {
+            return CostSearch { cost: Some(cost), visited };
+        }
+        visited += 1;
+        for next in successors(&state) {
+            if first_reach(&next) {
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+    CostSearch { cost: None, visited }
+}
+
+/// Cheapest path from `start` to a state matching `is_goal`, with `successors` giving each next state
+/// with the cost of getting there.
+pub fn dijkstra<S, C, I>(start: S, successors: impl FnMut(&S) -> I, is_goal: impl FnMut(&S) -> bool) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// Like [`dijkstra`], expanding first the states whose cost plus `heuristic` is the lowest. The
+/// heuristic must never overestimate the remaining cost for the path to be the cheapest.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&start), C::default(), 0))]);
+    let mut nodes = Nodes::new(start, C::default());
+    let mut visited = 0;
+    while let Some(Reverse((_, cost, i))) = queue.pop() {
+        if cost > nodes.nodes[i].cost {
+            continue; // a cheaper way to this state was found after queueing it
+        }
+        if is_goal(&nodes.nodes[i].state) {
+            return Search { path: Some(nodes.path(i)), visited };
+        }
+        visited += 1;
+        for (next, step_cost) in successors(&nodes.nodes[i].state) {
+            let next_cost = cost + step_cost;
+            let estimate = next_cost + heuristic(&next);
+            if let Some(n) = nodes.reach(next, i, next_cost) {
+                queue.push(Reverse((estimate, next_cost, n)));
+            }
+        }
+    }
+    Search { path: None, visited }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bfs() {
+        // steps of +1 or *2
+        let search = bfs(1u32, |&n| [n + 1, n * 2], |&n| n == 10);
+        let path = search.path.unwrap();
+        assert_eq!(path.states, [1, 2, 4, 5, 10]);
+        assert_eq!(path.cost, 4);
+        assert!(search.visited > 4);
+
+        let search = bfs(1u32, |&n| (n < 5).then_some(n + 1), |&n| n == 10);
+        assert_eq!(search.path, None);
+        assert_eq!(search.visited, 5);
+
+        let search = bfs_cost(1u32, |&n| [n + 1, n * 2], |&n| n == 10);
+        assert_eq!(search.cost, Some(4));
+        let mut reached = [false; 6];
+        let search = bfs_cost_by(1usize, |&n| (n < 5).then_some(n + 1), |&n| n == 10, |&n| !std::mem::replace(&mut reached[n], true));
+        assert_eq!(search, CostSearch { cost: None, visited: 5 });
+    }
+
+    #[test]
+    fn test_dijkstra_astar() {
+        // the direct edge to 3 is more expensive than going through 1 and 2
+        let edges = |&n: &u8| match n {
+            0 => vec![(1, 1), (3, 10)],
+            1 => vec![(2, 2)],
+            2 => vec![(3, 3)],
+            _ => vec![],
+        };
+        let search = dijkstra(0, edges, |&n| n == 3);
+        assert_eq!(search.path, Some(Path { states: vec![0, 1, 2, 3], cost: 6 }));
+
+        let search = astar(0, edges, |&n| 3 - n as u32, |&n| n == 3);
+        assert_eq!(search.path.unwrap().cost, 6);
+        assert_eq!(search.visited, 3);
+    }
+}
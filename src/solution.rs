@@ -0,0 +1,67 @@
+use std::any::Any;
+
+use crate::answer::Answer;
+use crate::input::InputSource;
+use crate::parser::ParseError;
+
+/// A puzzle solution, split in the parsing of the input and the solving of each part.
+pub trait Solution {
+    const DAY: u8;
+    type Input: Send + 'static;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    /// Parses the example of the puzzle, for the days whose example asks something different from the
+    /// puzzle input, such as another row to check.
+    fn parse_example(input: &str) -> Result<Self::Input, ParseError> {
+        Self::parse(input)
+    }
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+
+    fn solve(input: &str) -> Result<(Answer, Answer), ParseError> {
+        let input = Self::parse(input).map_err(|e| e.with_day(Self::DAY))?;
+        Ok((Self::part1(&input), Self::part2(&input)))
+    }
+}
+
+/// Object-safe version of [`Solution`], so solutions of different days can be stored together.
+/// The parsed input is type-erased and must only be passed back to the same solution.
+pub trait DynSolution: Sync {
+    fn parse(&self, input: &str) -> Result<Box<dyn Any + Send>, ParseError>;
+    fn parse_example(&self, input: &str) -> Result<Box<dyn Any + Send>, ParseError>;
+    fn part1(&self, input: &dyn Any) -> Answer;
+    fn part2(&self, input: &dyn Any) -> Answer;
+
+    /// Parses `input` as read from `source`, with [`parse_example`](Self::parse_example) for the
+    /// examples.
+    fn parse_from(&self, input: &str, source: &InputSource) -> Result<Box<dyn Any + Send>, ParseError> {
+        match source {
+            InputSource::Example => self.parse_example(input),
+            _ => self.parse(input),
+        }
+    }
+}
+
+impl<S: Solution + Sync> DynSolution for S {
+    fn parse(&self, input: &str) -> Result<Box<dyn Any + Send>, ParseError> {
+        match S::parse(input) {
+            Ok(input) => Ok(Box::new(input)),
+            Err(err) => Err(err.with_day(S::DAY)),
+        }
+    }
+
+    fn parse_example(&self, input: &str) -> Result<Box<dyn Any + Send>, ParseError> {
+        match S::parse_example(input) {
+            Ok(input) => Ok(Box::new(input)),
+            Err(err) => Err(err.with_day(S::DAY)),
+        }
+    }
+
+    fn part1(&self, input: &dyn Any) -> Answer {
+        S::part1(input.downcast_ref().expect("input parsed by a different solution"))
+    }
+
+    fn part2(&self, input: &dyn Any) -> Answer {
+        S::part2(input.downcast_ref().expect("input parsed by a different solution"))
+    }
+}
@@ -0,0 +1,101 @@
+//! Animated terminal rendering of the simulations, enabled by `aoc run --visualize`.
+//!
+//! Days render a frame at each step of their simulation with [`frame`], which redraws it in place of
+//! the previous one with ANSI escape codes and waits to keep to the frame rate. Frames are only built
+//! when [`enabled`] is set, so the simulations run at full speed otherwise.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::geom::{vec2, Bounds, Vec2};
+
+/// Frames per second, 0 when disabled.
+static FRAME_RATE: AtomicU32 = AtomicU32::new(0);
+/// When the last frame was drawn, `None` before the first one.
+static LAST_FRAME: Mutex<Option<Instant>> = Mutex::new(None);
+
+pub const DEFAULT_FRAME_RATE: u32 = 30;
+
+/// Size of the part of a simulation shown by [`view`], to fit in a terminal.
+pub const VIEW_SIZE: Vec2<i64> = vec2(80, 40);
+
+/// Enables the visualisation at `fps` frames per second, or disables it if 0 (the default).
+pub fn set_frame_rate(fps: u32) {
+    FRAME_RATE.store(fps, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    FRAME_RATE.load(Ordering::Relaxed) > 0
+}
+
+/// Draws `body` under a `title` line in place of the previous frame, after waiting for the previous
+/// one to have been shown for a frame.
+pub fn frame(title: &str, body: &str) {
+    let fps = FRAME_RATE.load(Ordering::Relaxed);
+    if fps == 0 {
+        return;
+    }
+
+    let mut last = LAST_FRAME.lock().unwrap();
+    let mut out = String::new();
+    match *last {
+        // clear the screen the first time, then only overwrite the lines to avoid flickering
+        None => out.push_str("\x1b[2J\x1b[H"),
+        Some(t) => {
+            thread::sleep((t + Duration::from_secs(1) / fps).saturating_duration_since(Instant::now()));
+            out.push_str("\x1b[H");
+        },
+    }
+    for line in [title].into_iter().chain(body.lines()) {
+        out.push_str(line);
+        out.push_str("\x1b[K\n");
+    }
+    out.push_str("\x1b[J");
+
+    let mut stdout = io::stdout().lock();
+    // a closed stdout only loses the animation
+    let _ = stdout.write_all(out.as_bytes()).and_then(|_| stdout.flush());
+    *last = Some(Instant::now());
+}
+
+/// Bounds of a [`VIEW_SIZE`] view centered on `center`, moved to stay inside `limits` when given
+/// and shrunk to them if they are smaller.
+pub fn view(center: Vec2<i64>, limits: Option<Bounds<Vec2<i64>>>) -> Bounds<Vec2<i64>> {
+    let mut min = center - VIEW_SIZE / 2;
+    if let Some(limits) = limits {
+        let max_min = limits.max - VIEW_SIZE + vec2(1, 1);
+        min = vec2(min.x.min(max_min.x).max(limits.min.x), min.y.min(max_min.y).max(limits.min.y));
+        let max = min + VIEW_SIZE - vec2(1, 1);
+        return Bounds { min, max: vec2(max.x.min(limits.max.x), max.y.min(limits.max.y)) };
+    }
+    Bounds { min, max: min + VIEW_SIZE - vec2(1, 1) }
+}
+
+/// One line per row of `bounds`, with `cell` giving the character at each position.
+pub fn render(bounds: Bounds<Vec2<i64>>, mut cell: impl FnMut(Vec2<i64>) -> char) -> String {
+    let mut s = String::new();
+    for y in bounds.min.y..=bounds.max.y {
+        s.extend((bounds.min.x..=bounds.max.x).map(|x| cell(vec2(x, y))));
+        s.push('\n');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view() {
+        let limits = Bounds { min: vec2(0, 0), max: vec2(99, 29) };
+        assert_eq!(view(vec2(50, 10), Some(limits)), Bounds { min: vec2(10, 0), max: vec2(89, 29) });
+        assert_eq!(view(vec2(95, 10), Some(limits)).min, vec2(20, 0));
+        assert_eq!(view(vec2(0, 0), None), Bounds { min: vec2(-40, -20), max: vec2(39, 19) });
+
+        let bounds = Bounds { min: vec2(1, 1), max: vec2(3, 2) };
+        assert_eq!(render(bounds, |p| if p.x == p.y { '#' } else { '.' }), "#..\n.#.\n");
+    }
+}
@@ -0,0 +1,29 @@
+//! The allocation columns of the profiler, in their own test binary as they need `CountingAlloc` to be
+//! the global allocator.
+
+use aoc_2022::profiler::{self, span, CountingAlloc};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+#[test]
+fn test_alloc_stats() {
+    {
+        let _outer = span("outer");
+        {
+            let _inner = span("inner");
+            let v = std::hint::black_box(vec![0u8; 1000]);
+            drop(v);
+        }
+        let v = std::hint::black_box(vec![0u8; 500]);
+        drop(v);
+    }
+
+    assert!(profiler::alloc_tracking());
+    let stats = profiler::summary();
+    let (outer, inner) = (stats[0].alloc, stats[1].alloc);
+    assert!(inner.allocs >= 1 && inner.bytes >= 1000 && inner.peak_bytes >= 1000);
+    assert!(outer.allocs > inner.allocs && outer.bytes >= inner.bytes + 500);
+    // the two vectors were never alive at the same time
+    assert!(outer.peak_bytes < 1500);
+}